@@ -10,12 +10,19 @@ use crate::{
     },
 };
 
+pub use editor::BoardEditor;
+pub use packed::PackedBoard;
+pub use parser::ParseFenError;
+
 #[cfg(test)]
 mod tests;
 
+mod editor;
 mod makemove;
 mod movegen;
+mod packed;
 mod parser;
+mod san;
 mod see;
 
 /// Captures essential information needed to efficiently revert the board to
@@ -405,6 +412,45 @@ impl Board {
         self.checking_squares(self.moved_piece(mv).piece_type()).contains(mv.to())
     }
 
+    /// Checks if the move gives check to the opponent's king.
+    ///
+    /// Unlike [`Board::is_direct_check`], this is exact: it also detects discovered checks
+    /// (including those uncovered by the pawn captured en passant), checks by the promoted
+    /// piece, and checks delivered by the rook when castling.
+    pub fn gives_check(&self, mv: Move) -> bool {
+        let stm = self.side_to_move();
+        let king = self.king_square(!stm);
+        let (from, to) = (mv.from(), mv.to());
+
+        let mut occupancies = self.occupancies() & !from.to_bb();
+        let mut moved = from.to_bb();
+
+        let (square, piece_type) = if mv.is_castling() {
+            let (rook_from, rook_to) = self.get_castling_rook(to);
+            occupancies = (occupancies & !rook_from.to_bb()) | rook_to.to_bb();
+            moved |= rook_from.to_bb();
+            (rook_to, PieceType::Rook)
+        } else if mv.is_promotion() {
+            (to, mv.promo_piece_type())
+        } else {
+            (to, self.type_on(from))
+        };
+
+        if mv.is_en_passant() {
+            occupancies &= !mv.capture_sq().to_bb();
+        }
+        occupancies |= to.to_bb();
+
+        if attacks(Piece::new(stm, piece_type), square, occupancies).contains(king) {
+            return true;
+        }
+
+        let diagonal = self.colored_pieces2(stm, PieceType::Bishop, PieceType::Queen) & !moved;
+        let orthogonal = self.colored_pieces2(stm, PieceType::Rook, PieceType::Queen) & !moved;
+
+        !((bishop_attacks(king, occupancies) & diagonal) | (rook_attacks(king, occupancies) & orthogonal)).is_empty()
+    }
+
     pub fn update_threats(&mut self) {
         // The king is excluded from the occupancy bitboard when computing threats,
         // letting sliders "see through" it as if the king weren't blocking their path.
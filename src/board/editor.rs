@@ -0,0 +1,142 @@
+use super::{Board, parser::ParseFenError};
+use crate::types::{Color, Piece, Square};
+
+/// A position under construction, edited square by square and turned into a `Board` once complete.
+///
+/// Edits are not checked individually, so the position may be illegal in between. Finishing
+/// the edit validates the whole position the same way as [`Board::from_fen_strict`] and
+/// derives the threats, hash keys and castling data from scratch.
+#[derive(Clone)]
+pub struct BoardEditor {
+    mailbox: [Piece; Square::NUM],
+    side_to_move: Color,
+    castling: String,
+    en_passant: Square,
+    fiftymove_clock: u8,
+    fullmove_number: usize,
+    frc: bool,
+}
+
+impl Default for BoardEditor {
+    fn default() -> Self {
+        Self {
+            mailbox: [Piece::None; Square::NUM],
+            side_to_move: Color::White,
+            castling: "-".to_string(),
+            en_passant: Square::None,
+            fiftymove_clock: 0,
+            fullmove_number: 1,
+            frc: false,
+        }
+    }
+}
+
+impl BoardEditor {
+    pub fn piece_on(&self, square: Square) -> Piece {
+        self.mailbox[square]
+    }
+
+    pub fn place(&mut self, piece: Piece, square: Square) {
+        self.mailbox[square] = piece;
+    }
+
+    /// Removes the piece on the square, returning it.
+    pub fn remove(&mut self, square: Square) -> Piece {
+        std::mem::replace(&mut self.mailbox[square], Piece::None)
+    }
+
+    /// Removes all pieces, keeping the other fields untouched.
+    pub fn clear(&mut self) {
+        self.mailbox = [Piece::None; Square::NUM];
+    }
+
+    pub fn set_side_to_move(&mut self, color: Color) {
+        self.side_to_move = color;
+    }
+
+    /// Sets the castling rights, written as in a FEN string (`KQkq`, `HAha` or `-`).
+    pub fn set_castling(&mut self, rights: &str) {
+        self.castling = if rights.is_empty() { "-".to_string() } else { rights.to_string() };
+    }
+
+    /// Sets the en passant square, or clears it with `Square::None`.
+    pub fn set_en_passant(&mut self, square: Square) {
+        self.en_passant = square;
+    }
+
+    /// Sets whether castling rights and moves follow Chess960 conventions.
+    pub fn set_frc(&mut self, frc: bool) {
+        self.frc = frc;
+    }
+
+    pub fn set_fiftymove_clock(&mut self, clock: u8) {
+        self.fiftymove_clock = clock;
+    }
+
+    pub fn set_fullmove_number(&mut self, number: usize) {
+        self.fullmove_number = number;
+    }
+
+    /// Returns the FEN string of the position as edited so far.
+    pub fn to_fen(&self) -> String {
+        let mut placement = Vec::new();
+
+        for rank in (0..8).rev() {
+            let mut row = String::new();
+            let mut empty_count = 0;
+
+            for file in 0..8 {
+                let piece = self.mailbox[Square::from_rank_file(rank, file)];
+                if piece == Piece::None {
+                    empty_count += 1;
+                    continue;
+                }
+
+                if empty_count > 0 {
+                    row.push_str(&empty_count.to_string());
+                    empty_count = 0;
+                }
+                row.push_str(&piece.to_string());
+            }
+
+            if empty_count > 0 {
+                row.push_str(&empty_count.to_string());
+            }
+            placement.push(row);
+        }
+
+        format!(
+            "{} {} {} {} {} {}",
+            placement.join("/"),
+            self.side_to_move,
+            self.castling,
+            self.en_passant,
+            self.fiftymove_clock,
+            self.fullmove_number
+        )
+    }
+
+    /// Validates the position and builds the board.
+    ///
+    /// Offsets in the returned error refer to the string produced by [`BoardEditor::to_fen`].
+    pub fn finish(&self) -> Result<Board, ParseFenError> {
+        let mut board = Board::from_fen_strict(&self.to_fen())?;
+        board.set_frc(self.frc);
+        Ok(board)
+    }
+}
+
+impl Board {
+    /// Starts editing a copy of the position.
+    pub fn edit(&self) -> BoardEditor {
+        BoardEditor {
+            mailbox: self.mailbox,
+            side_to_move: self.side_to_move(),
+            castling: self.state.castling.to_string(self),
+            en_passant: self.state.en_passant,
+            fiftymove_clock: self.state.fiftymove_clock,
+            fullmove_number: self.fullmove_number().max(1),
+            frc: self.frc,
+        }
+    }
+}
@@ -37,6 +37,120 @@ impl super::Board {
         self.generate_moves(list, MovegenKind::Noisy);
     }
 
+    /// Appends the moves that get out of check, captures of the checker and interpositions
+    /// included. Only the king may move when in double check.
+    pub fn append_evasions(&self, list: &mut MoveList) {
+        debug_assert!(self.in_check());
+
+        self.append_all_moves(list);
+    }
+
+    /// Appends the quiet moves that give check, such as for searching them in quiescence.
+    ///
+    /// Direct checks are generated only towards the checking squares of each piece, and pieces
+    /// blocking one of our sliders from the enemy king also move off that line. Underpromotions
+    /// and castling are rare enough to be filtered with [`Board::gives_check`](super::Board::gives_check).
+    pub fn append_quiet_checks(&self, list: &mut MoveList) {
+        let stm = self.side_to_move();
+        let occupancies = self.occupancies();
+        let empty = !occupancies;
+        let king_sq = self.king_square(stm);
+        let their_king = self.king_square(!stm);
+        let candidates = self.discovered_check_candidates();
+
+        if candidates.contains(king_sq) {
+            let discovered = !ray_pass(their_king, king_sq);
+            list.push_setwise(
+                king_sq,
+                king_attacks(king_sq) & !self.all_threats() & empty & discovered,
+                MoveKind::Normal,
+            );
+        }
+
+        let checks = |pt: PieceType, from: Square| {
+            let discovered = if candidates.contains(from) { !ray_pass(their_king, from) } else { Bitboard::default() };
+            self.checking_squares(pt) | discovered
+        };
+
+        if self.checkers().is_multiple() {
+            return;
+        }
+
+        let target = empty
+            & if self.in_check() { between(king_sq, self.checkers().lsb()) | self.checkers() } else { Bitboard::ALL };
+        let pinned = self.pinned(stm);
+
+        for knight in self.colored_pieces(stm, PieceType::Knight) & !pinned {
+            list.push_setwise(
+                knight,
+                knight_attacks(knight) & target & checks(PieceType::Knight, knight),
+                MoveKind::Normal,
+            );
+        }
+
+        let bishops = self.colored_pieces(stm, PieceType::Bishop);
+        let rooks = self.colored_pieces(stm, PieceType::Rook);
+        let queens = self.colored_pieces(stm, PieceType::Queen);
+
+        self.collect::<_>(list, target, bishops, MoveKind::Normal, pinned, |sq| {
+            bishop_attacks(sq, occupancies) & checks(PieceType::Bishop, sq)
+        });
+        self.collect::<_>(list, target, rooks, MoveKind::Normal, pinned, |sq| {
+            rook_attacks(sq, occupancies) & checks(PieceType::Rook, sq)
+        });
+        self.collect::<_>(list, target, queens, MoveKind::Normal, pinned, |sq| {
+            queen_attacks(sq, occupancies) & checks(PieceType::Queen, sq)
+        });
+
+        // A pawn push stays on its file, so only uncovers a check along a rank or diagonal
+        let up = Square::UP[stm];
+        let pawns = self.colored_pieces(stm, PieceType::Pawn);
+        let discovering = pawns & candidates & !Bitboard::file(their_king.file());
+
+        let pushed_pawns = (pawns & (!pinned | Bitboard::file(king_sq.file()))).shift(up) & empty;
+        let promotions = pushed_pawns & Bitboard::BOTH_HOME_ROWS & target;
+        let single_pushes = pushed_pawns & !Bitboard::BOTH_HOME_ROWS;
+        let double_pushes = (single_pushes & Bitboard::THIRD_RANK[stm]).shift(up) & empty;
+
+        let pawn_checks = self.checking_squares(PieceType::Pawn);
+        list.push_pawns_setwise(up, single_pushes & target & (pawn_checks | discovering.shift(up)), MoveKind::Normal);
+        list.push_pawns_setwise(
+            up * 2,
+            double_pushes & target & (pawn_checks | discovering.shift(up * 2)),
+            MoveKind::DoublePush,
+        );
+
+        let mut rare = MoveList::new();
+        for kind in [MoveKind::PromotionR, MoveKind::PromotionB, MoveKind::PromotionN] {
+            rare.push_pawns_setwise(up, promotions, kind);
+        }
+        self.collect_castling(&mut rare);
+
+        for entry in rare.iter().filter(|entry| self.gives_check(entry.mv)) {
+            list.push(entry.mv.from(), entry.mv.to(), entry.mv.kind());
+        }
+    }
+
+    /// Returns our pieces that are the only blocker between one of our sliders and the enemy king.
+    fn discovered_check_candidates(&self) -> Bitboard {
+        let stm = self.side_to_move();
+        let king = self.king_square(!stm);
+
+        let diagonal = self.colored_pieces2(stm, PieceType::Bishop, PieceType::Queen);
+        let orthogonal = self.colored_pieces2(stm, PieceType::Rook, PieceType::Queen);
+        let snipers =
+            (bishop_attacks(king, self.colors(!stm)) & diagonal) | (rook_attacks(king, self.colors(!stm)) & orthogonal);
+
+        let mut candidates = Bitboard::default();
+        for sniper in snipers {
+            let blockers = between(king, sniper) & self.occupancies();
+            if blockers.popcount() == 1 {
+                candidates |= blockers;
+            }
+        }
+        candidates
+    }
+
     fn generate_moves(&self, list: &mut MoveList, mgkind: MovegenKind) {
         let stm = self.side_to_move();
         let occupancies = self.occupancies();
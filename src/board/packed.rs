@@ -0,0 +1,164 @@
+use super::Board;
+use crate::types::{Bitboard, CastlingKind, Color, Piece, PieceType, Square};
+
+/// Compact fixed-size encoding of a position together with a search score and a game result,
+/// used for storing training data.
+///
+/// The layout of the 32 bytes is as follows (all integers are little-endian):
+///
+/// | Offset | Size | Contents                                                                |
+/// | ------ | ---- | ----------------------------------------------------------------------- |
+/// | 0      | 8    | Occupancy bitboard                                                      |
+/// | 8      | 16   | Pieces in occupancy order, one nibble each (`color << 3 \| piece type`) |
+/// | 24     | 1    | Side to move in the high bit, en passant square in the low bits         |
+/// | 25     | 1    | Halfmove clock                                                          |
+/// | 26     | 2    | Fullmove number                                                         |
+/// | 28     | 2    | Score in centipawns from white's perspective                            |
+/// | 30     | 1    | Game result from white's perspective (0 = loss, 1 = draw, 2 = win)      |
+/// | 31     | 1    | Unused                                                                  |
+///
+/// Rooks that still carry a castling right are stored with the piece type `UNMOVED_ROOK`,
+/// which encodes castling for both standard chess and Chess960.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct PackedBoard {
+    occupancy: u64,
+    pieces: [u8; 16],
+    stm_ep: u8,
+    halfmove_clock: u8,
+    fullmove_number: u16,
+    pub score: i16,
+    pub result: u8,
+}
+
+impl PackedBoard {
+    pub const SIZE: usize = 32;
+
+    const UNMOVED_ROOK: u8 = 6;
+    const NO_EN_PASSANT: u8 = 64;
+
+    pub fn pack(board: &Board, score: i16, result: u8) -> Self {
+        let occupancy = board.occupancies();
+        let mut pieces = [0; 16];
+
+        let mut castling_rooks = 0u64;
+        for kind in CastlingKind::KINDS.iter().flatten() {
+            if board.castling().is_allowed(*kind) {
+                castling_rooks |= 1 << board.castling_rooks[*kind] as u64;
+            }
+        }
+
+        for (index, square) in occupancy.into_iter().enumerate() {
+            let piece = board.piece_on(square);
+            let piece_type = match piece.piece_type() {
+                PieceType::Rook if castling_rooks & (1 << square as u64) != 0 => Self::UNMOVED_ROOK,
+                piece_type => piece_type as u8,
+            };
+
+            pieces[index / 2] |= (((piece.color() as u8) << 3) | piece_type) << (4 * (index % 2));
+        }
+
+        let en_passant =
+            if board.en_passant() == Square::None { Self::NO_EN_PASSANT } else { board.en_passant() as u8 };
+
+        Self {
+            occupancy: occupancy.0,
+            pieces,
+            stm_ep: ((board.side_to_move() == Color::Black) as u8) << 7 | en_passant,
+            halfmove_clock: board.fiftymove_clock(),
+            fullmove_number: board.fullmove_number() as u16,
+            score,
+            result,
+        }
+    }
+
+    pub fn to_bytes(self) -> [u8; Self::SIZE] {
+        let mut bytes = [0; Self::SIZE];
+
+        bytes[0..8].copy_from_slice(&self.occupancy.to_le_bytes());
+        bytes[8..24].copy_from_slice(&self.pieces);
+        bytes[24] = self.stm_ep;
+        bytes[25] = self.halfmove_clock;
+        bytes[26..28].copy_from_slice(&self.fullmove_number.to_le_bytes());
+        bytes[28..30].copy_from_slice(&self.score.to_le_bytes());
+        bytes[30] = self.result;
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> Self {
+        let mut pieces = [0; 16];
+        pieces.copy_from_slice(&bytes[8..24]);
+
+        Self {
+            occupancy: u64::from_le_bytes(bytes[0..8].try_into().unwrap()),
+            pieces,
+            stm_ep: bytes[24],
+            halfmove_clock: bytes[25],
+            fullmove_number: u16::from_le_bytes([bytes[26], bytes[27]]),
+            score: i16::from_le_bytes([bytes[28], bytes[29]]),
+            result: bytes[30],
+        }
+    }
+
+    /// Reconstructs the position, returning `None` if the encoding is malformed.
+    pub fn unpack(&self) -> Option<Board> {
+        let mut mailbox = [None; Square::NUM];
+        let mut castling = String::new();
+
+        for (index, square) in Bitboard(self.occupancy).into_iter().enumerate() {
+            let nibble = (self.pieces[index / 2] >> (4 * (index % 2))) & 0xF;
+
+            let color = if nibble & 0b1000 != 0 { Color::Black } else { Color::White };
+            let piece_type = match nibble & 0b0111 {
+                Self::UNMOVED_ROOK => {
+                    let file = (b'A' + square.file() as u8) as char;
+                    castling.push(if color == Color::White { file } else { file.to_ascii_lowercase() });
+                    PieceType::Rook
+                }
+                piece_type if (piece_type as usize) < PieceType::NUM => PieceType::new(piece_type as usize),
+                _ => return None,
+            };
+
+            mailbox[square] = Some(Piece::new(color, piece_type));
+        }
+
+        let mut placement = String::new();
+        for rank in (0..8).rev() {
+            let mut empty = 0;
+            for file in 0..8 {
+                match mailbox[Square::from_rank_file(rank, file)] {
+                    Some(piece) => {
+                        if empty > 0 {
+                            placement.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        placement.push_str(&piece.to_string());
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                placement.push_str(&empty.to_string());
+            }
+            if rank > 0 {
+                placement.push('/');
+            }
+        }
+
+        if castling.is_empty() {
+            castling.push('-');
+        }
+
+        let side_to_move = if self.stm_ep & 0x80 != 0 { 'b' } else { 'w' };
+        let en_passant = match self.stm_ep & 0x7F {
+            Self::NO_EN_PASSANT => "-".to_string(),
+            square if square < Self::NO_EN_PASSANT => Square::new(square).to_string(),
+            _ => return None,
+        };
+
+        let fen = format!(
+            "{placement} {side_to_move} {castling} {en_passant} {} {}",
+            self.halfmove_clock, self.fullmove_number
+        );
+        Board::from_fen(&fen).ok()
+    }
+}
@@ -1,17 +1,70 @@
 use super::Board;
 use crate::{
     lookup::{between, ray_pass},
-    types::{CastlingKind, Color, HOME_RANK, KING_TO_FILE, Piece, PieceType, ROOK_TO_FILE, Square},
+    types::{Bitboard, CastlingKind, Color, File, HOME_RANK, KING_TO_FILE, Piece, PieceType, ROOK_TO_FILE, Square},
 };
 
-#[derive(Debug)]
+/// Names of the FEN fields, in the order they appear in the string.
+const FIELDS: [&str; 6] =
+    ["piece placement", "side to move", "castling rights", "en passant square", "halfmove clock", "fullmove number"];
+
+/// An error encountered while parsing a FEN string.
+///
+/// Errors tied to a part of the string carry `at`, the byte offset where it starts.
+#[derive(Debug, PartialEq, Eq)]
 pub enum ParseFenError {
     /// The FEN string is missing piece placement data.
     MissingPlacementData,
+    /// The FEN string ends before a required field.
+    MissingField { field: &'static str },
+    /// The FEN string has more fields than expected.
+    TrailingData { at: usize },
+    /// The piece placement does not consist of eight ranks.
+    InvalidRankCount { count: usize, at: usize },
+    /// A rank does not describe exactly eight squares.
+    InvalidRankLength { rank: u8, at: usize },
     /// The FEN string contains an invalid piece type character.
-    InvalidPieceType,
+    InvalidPieceType { symbol: char, at: usize },
     /// The FEN string contains an invalid active color.
-    InvalidActiveColor,
+    InvalidActiveColor { at: usize },
+    /// A castling right is malformed, repeated, or has no matching king and rook.
+    InvalidCastlingRights { symbol: char, at: usize },
+    /// The en passant square is malformed or does not follow a double pawn push.
+    InvalidEnPassant { at: usize },
+    /// The halfmove clock or fullmove number is not a number.
+    InvalidMoveCounter { field: &'static str, at: usize },
+    /// A side does not have exactly one king.
+    InvalidKingCount { color: Color, count: usize },
+    /// A pawn is placed on the first or last rank.
+    PawnOnBackRank { square: Square },
+    /// The side that is not to move is in check.
+    OpponentInCheck,
+}
+
+impl std::fmt::Display for ParseFenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = |color: &Color| if *color == Color::White { "white" } else { "black" };
+
+        match self {
+            Self::MissingPlacementData => write!(f, "missing piece placement"),
+            Self::MissingField { field } => write!(f, "missing {field}"),
+            Self::TrailingData { at } => write!(f, "unexpected data at column {}", at + 1),
+            Self::InvalidRankCount { count, at } => write!(f, "expected 8 ranks, found {count} at column {}", at + 1),
+            Self::InvalidRankLength { rank, at } => {
+                write!(f, "rank {} does not have 8 squares at column {}", rank + 1, at + 1)
+            }
+            Self::InvalidPieceType { symbol, at } => write!(f, "invalid piece '{symbol}' at column {}", at + 1),
+            Self::InvalidActiveColor { at } => write!(f, "invalid side to move at column {}", at + 1),
+            Self::InvalidCastlingRights { symbol, at } => {
+                write!(f, "invalid castling right '{symbol}' at column {}", at + 1)
+            }
+            Self::InvalidEnPassant { at } => write!(f, "invalid en passant square at column {}", at + 1),
+            Self::InvalidMoveCounter { field, at } => write!(f, "invalid {field} at column {}", at + 1),
+            Self::InvalidKingCount { color, count } => write!(f, "{} has {count} kings", name(color)),
+            Self::PawnOnBackRank { square } => write!(f, "pawn on back rank at {square}"),
+            Self::OpponentInCheck => write!(f, "side not to move is in check"),
+        }
+    }
 }
 
 impl Board {
@@ -19,45 +72,142 @@ impl Board {
     ///
     /// The parser is not very strict and will accept some invalid FEN strings,
     /// it's the responsibility of the GUI to ensure the FEN string is valid.
+    /// Only errors that would leave the board in an unusable state are reported,
+    /// see [`Board::from_fen_strict`] for a full validation.
     ///
     /// [fen]: https://en.wikipedia.org/wiki/Forsyth%E2%80%93Edwards_Notation
     pub fn from_fen(fen: &str) -> Result<Self, ParseFenError> {
+        Self::parse_fen(fen, false)
+    }
+
+    /// Parses a FEN string, rejecting anything that is malformed or does not describe a legal position.
+    ///
+    /// The halfmove clock and fullmove number may be omitted, as is common in EPD files.
+    pub fn from_fen_strict(fen: &str) -> Result<Self, ParseFenError> {
+        Self::parse_fen(fen, true)
+    }
+
+    /// Returns whether the castling field of a FEN string names rook files (Shredder-FEN or
+    /// X-FEN) rather than `KQkq`, which only Chess960 positions require.
+    pub fn is_frc_fen(fen: &str) -> bool {
+        fen.split_whitespace()
+            .nth(2)
+            .is_some_and(|rights| rights.contains(|c: char| c.is_ascii_alphabetic() && !"KQkq".contains(c)))
+    }
+
+    fn parse_fen(fen: &str, strict: bool) -> Result<Self, ParseFenError> {
+        let offset = |part: &str| part.as_ptr() as usize - fen.as_ptr() as usize;
+
         let mut board = Self::default();
-        let mut parts = fen.split_whitespace();
+        let mut parts = fen.split_whitespace().map(|part| (offset(part), part));
+
+        let (_, placement) = parts.next().ok_or(ParseFenError::MissingPlacementData)?;
+        let count = placement.split('/').count();
 
-        let rows = parts.next().ok_or(ParseFenError::MissingPlacementData)?.split('/');
+        if count > 8 || (strict && count < 8) {
+            return Err(ParseFenError::InvalidRankCount { count, at: offset(placement) });
+        }
 
-        for (rank, row) in rows.rev().enumerate() {
+        for (rank, row) in placement.split('/').rev().enumerate() {
+            let rank = rank as u8;
             let mut file = 0;
 
-            for symbol in row.chars() {
+            for (index, symbol) in row.char_indices() {
+                let at = offset(row) + index;
+
                 if let Some(skip) = symbol.to_digit(10) {
+                    if strict && !(1..=8).contains(&skip) {
+                        return Err(ParseFenError::InvalidPieceType { symbol, at });
+                    }
                     file += skip as u8;
-                    continue;
+                } else {
+                    if file >= 8 {
+                        return Err(ParseFenError::InvalidRankLength { rank, at });
+                    }
+
+                    let piece = symbol.try_into().map_err(|()| ParseFenError::InvalidPieceType { symbol, at })?;
+                    let square = Square::from_rank_file(rank, file);
+
+                    board.add_piece(piece, square);
+                    board.state.material += piece.value();
+                    file += 1;
                 }
 
-                let piece = symbol.try_into().map_err(|()| ParseFenError::InvalidPieceType)?;
-                let square = Square::from_rank_file(rank as u8, file);
+                if file > 8 {
+                    return Err(ParseFenError::InvalidRankLength { rank, at });
+                }
+            }
+
+            if strict && file != 8 {
+                return Err(ParseFenError::InvalidRankLength { rank, at: offset(row) });
+            }
+        }
+
+        // The rest of the board relies on each side having exactly one king
+        for color in [Color::White, Color::Black] {
+            let count = board.colored_pieces(color, PieceType::King).popcount();
+            if count != 1 {
+                return Err(ParseFenError::InvalidKingCount { color, count });
+            }
+        }
 
-                board.add_piece(piece, square);
-                board.state.material += piece.value();
-                file += 1;
+        if strict {
+            let pawns = board.pieces(PieceType::Pawn) & Bitboard::BOTH_HOME_ROWS;
+            if !pawns.is_empty() {
+                return Err(ParseFenError::PawnOnBackRank { square: pawns.lsb() });
             }
         }
 
         let side_to_move = match parts.next() {
-            Some("w") => Color::White,
-            Some("b") => Color::Black,
-            _ => return Err(ParseFenError::InvalidActiveColor),
+            Some((_, "w")) => Color::White,
+            Some((_, "b")) => Color::Black,
+            Some((at, _)) => return Err(ParseFenError::InvalidActiveColor { at }),
+            None => return Err(ParseFenError::MissingField { field: FIELDS[1] }),
         };
 
-        board.set_castling(parts.next().unwrap());
+        match parts.next() {
+            Some((at, rights)) => board.set_castling(rights, at, strict)?,
+            None if strict => return Err(ParseFenError::MissingField { field: FIELDS[2] }),
+            None => (),
+        }
+
+        match parts.next() {
+            Some((_, "-")) => (),
+            Some((at, square)) => match Square::try_from(square) {
+                Ok(square) if !strict || board.is_valid_en_passant(square, side_to_move) => {
+                    board.state.en_passant = square;
+                }
+                _ if strict => return Err(ParseFenError::InvalidEnPassant { at }),
+                _ => (),
+            },
+            None if strict => return Err(ParseFenError::MissingField { field: FIELDS[3] }),
+            None => (),
+        }
+
+        let mut counter = |index: usize, max: usize| match parts.next() {
+            Some((at, value)) => match value.parse::<usize>() {
+                Ok(value) if value <= max => Ok(value),
+                _ if strict => Err(ParseFenError::InvalidMoveCounter { field: FIELDS[index], at }),
+                Ok(_) => Ok(max),
+                Err(_) => Ok(0),
+            },
+            None => Ok(0),
+        };
 
-        board.state.en_passant = parts.next().unwrap_or_default().try_into().unwrap_or_default();
-        board.state.fiftymove_clock = parts.next().unwrap_or_default().parse().unwrap_or_default();
-        let fullmove_number: usize = parts.next().unwrap_or_default().parse().unwrap_or_default();
+        board.state.fiftymove_clock = counter(4, u8::MAX as usize)? as u8;
+        let fullmove_number = counter(5, usize::MAX)?;
         board.halfmove_number = (2 * fullmove_number) + side_to_move as usize;
 
+        if strict && let Some((at, _)) = parts.next() {
+            return Err(ParseFenError::TrailingData { at });
+        }
+
+        // The side to move could capture the king, which the move generator doesn't expect
+        let checkers = board.attackers_to(board.king_square(!side_to_move), board.occupancies());
+        if !(checkers & board.colors(side_to_move)).is_empty() {
+            return Err(ParseFenError::OpponentInCheck);
+        }
+
         board.update_threats();
         board.update_hash_keys();
         board.validate_en_passant();
@@ -65,28 +215,41 @@ impl Board {
         Ok(board)
     }
 
-    fn set_castling(&mut self, rights: &str) {
-        for right in rights.chars() {
-            if !matches!(right.to_ascii_uppercase(), 'A'..='H' | 'K' | 'Q') {
-                continue;
-            }
+    /// Checks that the en passant square follows a double push of the side not to move.
+    fn is_valid_en_passant(&self, square: Square, side_to_move: Color) -> bool {
+        let pushed = square ^ 8;
+        let origin = if side_to_move == Color::White { square.shift(8) } else { square.shift(-8) };
+
+        Bitboard::SIXTH_RANK[side_to_move].contains(square)
+            && self.piece_on(square) == Piece::None
+            && self.piece_on(origin) == Piece::None
+            && self.colored_pieces(!side_to_move, PieceType::Pawn).contains(pushed)
+    }
+
+    fn set_castling(&mut self, rights: &str, at: usize, strict: bool) -> Result<(), ParseFenError> {
+        if rights == "-" {
+            return Ok(());
+        }
 
+        for (index, right) in rights.char_indices() {
             let color = if right.is_uppercase() { Color::White } else { Color::Black };
             let king_from = self.king_square(color);
-            let mut search_step = right.to_ascii_uppercase() as i8 - b'A' as i8 - king_from.file() as i8;
 
-            if right.eq_ignore_ascii_case(&'K') {
-                search_step = Square::RIGHT;
-            }
-            if right.eq_ignore_ascii_case(&'Q') {
-                search_step = Square::LEFT;
-            }
+            let side_of = |rook_from: Square| (rook_from > king_from) as usize;
 
-            let rook_from =
-                (ray_pass(king_from, king_from.shift(search_step)) & self.colored_pieces(color, PieceType::Rook)).lsb();
+            // Applying the same right twice would toggle it off again
+            let rook_from = self
+                .castling_rook(right, color)
+                .filter(|&rook_from| !self.state.castling.is_allowed(CastlingKind::KINDS[color][side_of(rook_from)]));
 
-            let king_side = (rook_from > king_from) as usize;
+            let Some(rook_from) = rook_from else {
+                if strict {
+                    return Err(ParseFenError::InvalidCastlingRights { symbol: right, at: at + index });
+                }
+                continue;
+            };
 
+            let king_side = side_of(rook_from);
             let rights = CastlingKind::KINDS[color][king_side];
 
             let king_to = Square::from_rank_file(HOME_RANK[color].clone() as u8, KING_TO_FILE[king_side].clone() as u8);
@@ -94,6 +257,26 @@ impl Board {
 
             self.set_castling_for(rights, king_from, king_to, rook_from, rook_to);
         }
+
+        Ok(())
+    }
+
+    /// Finds the rook a castling right refers to, if the king and the rook are on their home rank.
+    fn castling_rook(&self, right: char, color: Color) -> Option<Square> {
+        let king_from = self.king_square(color);
+        if king_from.rank() != HOME_RANK[color] {
+            return None;
+        }
+
+        let rooks = self.colored_pieces(color, PieceType::Rook);
+        let candidates = match right.to_ascii_uppercase() {
+            'K' if king_from.file() != File::H => ray_pass(king_from, king_from.shift(Square::RIGHT)) & rooks,
+            'Q' if king_from.file() != File::A => ray_pass(king_from, king_from.shift(Square::LEFT)) & rooks,
+            file @ 'A'..='H' => Square::from_rank_file(king_from.rank() as u8, file as u8 - b'A').to_bb() & rooks,
+            _ => return None,
+        };
+
+        (!candidates.is_empty()).then(|| candidates.lsb())
     }
 
     fn set_castling_for(
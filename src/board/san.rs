@@ -0,0 +1,79 @@
+use super::Board;
+use crate::types::{Move, PieceType, Square};
+
+impl Board {
+    /// Finds the legal move described by a [Standard Algebraic Notation][san] string.
+    ///
+    /// The parser is lenient: check and annotation suffixes, the capture sign and a missing
+    /// `=` before the promotion piece are all accepted. Ambiguous moves yield `None`.
+    ///
+    /// [san]: https://en.wikipedia.org/wiki/Algebraic_notation_(chess)
+    pub fn parse_san(&self, san: &str) -> Option<Move> {
+        let san = san.trim_end_matches(['+', '#', '!', '?']);
+        let moves = self.generate_all_moves();
+        let mut moves = moves.iter().map(|entry| entry.mv);
+
+        let castling = match san {
+            "O-O" | "0-0" => Some(true),
+            "O-O-O" | "0-0-0" => Some(false),
+            _ => None,
+        };
+
+        if let Some(kingside) = castling {
+            return moves.find(|mv| mv.is_castling() && mv.to().is_kingside() == kingside);
+        }
+
+        let (piece_type, san) = match san.chars().next()? {
+            'N' => (PieceType::Knight, &san[1..]),
+            'B' => (PieceType::Bishop, &san[1..]),
+            'R' => (PieceType::Rook, &san[1..]),
+            'Q' => (PieceType::Queen, &san[1..]),
+            'K' => (PieceType::King, &san[1..]),
+            _ => (PieceType::Pawn, san),
+        };
+
+        let (san, promotion) = match san.as_bytes() {
+            [.., b'=', promotion] | [.., b'1' | b'8', promotion @ (b'N' | b'B' | b'R' | b'Q')] => {
+                let promotion = match promotion {
+                    b'N' => PieceType::Knight,
+                    b'B' => PieceType::Bishop,
+                    b'R' => PieceType::Rook,
+                    b'Q' => PieceType::Queen,
+                    _ => return None,
+                };
+                (san[..san.len() - 1].trim_end_matches('='), Some(promotion))
+            }
+            _ => (san, None),
+        };
+
+        let san = san.replace(['x', '-', ':'], "");
+        let (disambiguation, to) = san.split_at_checked(san.len().checked_sub(2)?)?;
+        let to = Square::try_from(to).ok()?;
+
+        let mut file = None;
+        let mut rank = None;
+
+        for c in disambiguation.bytes() {
+            match c {
+                b'a'..=b'h' => file = Some(c - b'a'),
+                b'1'..=b'8' => rank = Some(c - b'1'),
+                _ => return None,
+            }
+        }
+
+        let mut candidates = moves.filter(|&mv| {
+            !mv.is_castling()
+                && mv.to() == to
+                && self.piece_on(mv.from()).piece_type() == piece_type
+                && file.is_none_or(|file| mv.from().file() as u8 == file)
+                && rank.is_none_or(|rank| mv.from().rank() as u8 == rank)
+                && match promotion {
+                    Some(promotion) => mv.is_promotion() && mv.promo_piece_type() == promotion,
+                    None => !mv.is_promotion(),
+                }
+        });
+
+        let mv = candidates.next()?;
+        candidates.next().is_none().then_some(mv)
+    }
+}
@@ -1,6 +1,6 @@
 use std::sync::Once;
 
-use super::{Board, NullBoardObserver};
+use super::{Board, BoardEditor, NullBoardObserver, PackedBoard};
 use crate::lookup;
 
 static LUT_INITIALIZED: Once = Once::new();
@@ -44,3 +44,221 @@ assert_perft!(
     position_5: "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8", [44, 1486, 62379, 2103487],
     position_6: "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10", [46, 2079, 89890, 3894594],
 );
+
+#[test]
+fn san_round_trip() {
+    prepare_lut();
+
+    let fens = [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "1rqbkrbn/1ppppp1p/1n6/p1N3p1/8/2P4P/PP1PPPP1/1RQBKRBN w FBfb - 0 9",
+    ];
+
+    for fen in fens {
+        let mut board = Board::from_fen(fen).unwrap();
+        board.set_frc(true);
+
+        for entry in board.generate_all_moves().iter() {
+            let san = entry.mv.to_san(&board);
+            assert_eq!(board.parse_san(&san), Some(entry.mv), "{fen}: {san}");
+        }
+    }
+}
+
+#[test]
+fn san_notation() {
+    prepare_lut();
+
+    let cases = [
+        ("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", "e1g1", "O-O"),
+        ("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", "d5e6", "dxe6"),
+        ("r6k/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7a8q", "bxa8=Q+"),
+        ("7k/6pp/8/8/8/8/8/R3K2R w - - 0 1", "a1a8", "Ra8#"),
+        ("7k/6pp/8/8/8/8/8/R4RK1 w - - 0 1", "a1d1", "Rad1"),
+        ("7k/8/8/8/R7/8/8/R3K3 w - - 0 1", "a1a2", "R1a2"),
+        ("2k5/8/8/8/8/8/5Q1Q/K6Q w - - 0 1", "h2g1", "Qh2g1"),
+        ("k7/8/8/8/8/8/8/1R2K2R w K - 0 1", "e1g1", "O-O"),
+    ];
+
+    for (fen, uci, san) in cases {
+        let board = Board::from_fen(fen).unwrap();
+        let mv = board.generate_all_moves().iter().map(|entry| entry.mv).find(|mv| mv.to_uci(&board) == uci).unwrap();
+
+        assert_eq!(mv.to_san(&board), san);
+        assert_eq!(board.parse_san(san), Some(mv));
+    }
+}
+
+#[test]
+fn packed_board_round_trip() {
+    prepare_lut();
+
+    let fens = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 37 120",
+        "1rqbkrbn/1ppppp1p/1n6/p1N3p1/8/2P4P/PP1PPPP1/1RQBKRBN w FBfb - 0 9",
+        "rbbnkqrn/pppppppp/8/8/8/8/PPPPPPPP/RBBNKQRN b AGag - 5 1",
+    ];
+
+    for fen in fens {
+        let mut board = Board::from_fen(fen).unwrap();
+        board.set_frc(Board::is_frc_fen(fen));
+
+        let packed = PackedBoard::pack(&board, -123, 2);
+        let decoded = PackedBoard::from_bytes(&packed.to_bytes());
+        assert_eq!(packed, decoded);
+        assert_eq!((decoded.score, decoded.result), (-123, 2));
+
+        let mut unpacked = decoded.unpack().unwrap();
+        unpacked.set_frc(board.is_frc());
+        assert_eq!(unpacked.to_fen(), board.to_fen());
+        assert_eq!(unpacked.hash(), board.hash());
+    }
+}
+
+#[test]
+fn strict_fen() {
+    use crate::{
+        board::parser::ParseFenError::*,
+        types::{Color, Square},
+    };
+
+    prepare_lut();
+
+    let valid = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2",
+        "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+        "4k3/8/8/8/8/8/8/4K3 b - -",
+    ];
+
+    for fen in valid {
+        assert!(Board::from_fen_strict(fen).is_ok(), "{fen}: {:?}", Board::from_fen_strict(fen).err());
+    }
+
+    let invalid = [
+        ("", MissingPlacementData),
+        ("4k3/8/8/8/8/8/8/4K3 w", MissingField { field: "castling rights" }),
+        ("4k3/8/8/8/8/8/8/4K3 w - - 0 1 e4", TrailingData { at: 30 }),
+        ("4k3/8/8/8/8/8/4K3 w - - 0 1", InvalidRankCount { count: 7, at: 0 }),
+        ("4k3/8/8/8/8/8/8/4K2 w - - 0 1", InvalidRankLength { rank: 0, at: 16 }),
+        ("4k3/8/8/8/8/8/8/4K4 w - - 0 1", InvalidRankLength { rank: 0, at: 18 }),
+        ("4k3/8/8/8/8/8/8/4X3 w - - 0 1", InvalidPieceType { symbol: 'X', at: 17 }),
+        ("4k3/8/8/8/8/8/8/4K3 x - - 0 1", InvalidActiveColor { at: 20 }),
+        ("4k3/8/8/8/8/8/8/4K3 w K - 0 1", InvalidCastlingRights { symbol: 'K', at: 22 }),
+        ("r3k2r/8/8/8/8/8/8/R3K2R w KQkqk - 0 1", InvalidCastlingRights { symbol: 'k', at: 30 }),
+        ("4k3/8/8/8/4P3/8/8/4K3 b - e4 0 1", InvalidEnPassant { at: 26 }),
+        ("4k3/8/8/8/8/8/8/4K3 b - e3 0 1", InvalidEnPassant { at: 24 }),
+        ("4k3/8/8/8/8/8/8/4K3 w - - x 1", InvalidMoveCounter { field: "halfmove clock", at: 26 }),
+        ("4k3/8/8/8/8/8/8/4K3 w - - 300 1", InvalidMoveCounter { field: "halfmove clock", at: 26 }),
+        ("8/8/8/8/8/8/8/4K3 w - - 0 1", InvalidKingCount { color: Color::Black, count: 0 }),
+        ("4k3/8/8/8/8/8/8/4K2P w - - 0 1", PawnOnBackRank { square: Square::H1 }),
+        ("4k3/8/8/8/8/8/8/4R1K1 w - - 0 1", OpponentInCheck),
+    ];
+
+    for (fen, error) in invalid {
+        assert_eq!(Board::from_fen_strict(fen).err(), Some(error), "{fen}");
+    }
+
+    // The lenient parser still accepts what it can make sense of
+    assert!(Board::from_fen("4k3/8/8/8/8/8/8/4K3 w K").is_ok());
+    assert_eq!(Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 300 1").unwrap().fiftymove_clock(), u8::MAX);
+    assert!(Board::from_fen("4k3/8/8/8/8/8/8/4K2P w - - 0 1 e4").is_ok());
+}
+
+#[test]
+fn board_editor() {
+    use crate::types::{Color, Piece, Square};
+
+    prepare_lut();
+
+    let board = Board::starting_position();
+    let mut editor = board.edit();
+    assert_eq!(editor.finish().map(|edited| edited.to_fen()), Ok(board.to_fen()));
+
+    editor.remove(Square::E2);
+    editor.place(Piece::WhitePawn, Square::E4);
+    editor.remove(Square::D7);
+    editor.place(Piece::BlackPawn, Square::D4);
+    editor.set_side_to_move(Color::Black);
+    editor.set_en_passant(Square::E3);
+
+    let edited = editor.finish().unwrap();
+    assert_eq!(edited.to_fen(), "rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+    assert_eq!(edited.hash(), Board::from_fen(&edited.to_fen()).unwrap().hash());
+
+    // Castling rights must still match the pieces once the edit is finished
+    editor.remove(Square::H1);
+    assert!(editor.finish().is_err());
+    editor.set_castling("Qkq");
+    assert!(editor.finish().is_ok());
+
+    let mut editor = BoardEditor::default();
+    editor.place(Piece::WhiteKing, Square::G1);
+    editor.place(Piece::BlackKing, Square::G8);
+    editor.place(Piece::WhiteRook, Square::A1);
+    editor.set_castling("Q");
+    assert_eq!(editor.finish().map(|edited| edited.to_fen()), Ok("6k1/8/8/8/8/8/8/R5K1 w Q - 0 1".to_string()));
+
+    editor.set_frc(true);
+    let edited = editor.finish().unwrap();
+    assert!(edited.is_frc());
+    assert_eq!(edited.to_fen(), "6k1/8/8/8/8/8/8/R5K1 w A - 0 1");
+    assert!(edited.edit().finish().unwrap().is_frc());
+}
+
+#[test]
+fn gives_check() {
+    use crate::types::MoveList;
+
+    fn walk(board: &mut Board, depth: usize) {
+        let mut quiet_checks = MoveList::new();
+        board.append_quiet_checks(&mut quiet_checks);
+
+        for entry in board.generate_all_moves().iter() {
+            let mv = entry.mv;
+            let gives_check = board.gives_check(mv);
+            let fen = board.to_fen();
+
+            board.make_move(mv, &mut NullBoardObserver);
+            assert_eq!(gives_check, board.in_check(), "{fen}: {}", mv.to_uci(board));
+            board.undo_move(mv);
+
+            let is_quiet_check = gives_check && !mv.is_noisy();
+            assert_eq!(quiet_checks.iter().any(|entry| entry.mv == mv), is_quiet_check, "{fen}");
+
+            if depth > 1 {
+                board.make_move(mv, &mut NullBoardObserver);
+                walk(board, depth - 1);
+                board.undo_move(mv);
+            }
+        }
+    }
+
+    prepare_lut();
+
+    let fens = [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "5k2/8/8/8/8/8/8/4K2R w K - 0 1",
+        "8/8/8/R2pP2k/8/8/8/K7 w - d6 0 1",
+        "1r2k2r/8/8/8/8/8/8/R3K1R1 w GAhb - 0 1",
+        "4k3/8/8/3Q4/4N3/8/8/K3R3 w - - 0 1",
+        "8/8/4k3/8/2P5/1B6/8/K7 w - - 0 1",
+        "7k/8/8/8/3K4/8/8/B7 w - - 0 1",
+        "3k4/1P6/8/8/8/8/8/K7 w - - 0 1",
+    ];
+
+    for fen in fens {
+        let mut board = Board::from_fen(fen).unwrap();
+        board.set_frc(Board::is_frc_fen(fen));
+        walk(&mut board, 3);
+    }
+}
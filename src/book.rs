@@ -0,0 +1,188 @@
+//! Reader for opening books in the [Polyglot][polyglot] format.
+//!
+//! A book is a file of 16-byte entries sorted by the Polyglot key of the position,
+//! each holding a move and its weight. Several entries may share the same key.
+//!
+//! [polyglot]: http://hgm.nubati.net/book_format.html
+
+use std::io::{Error, ErrorKind};
+
+use crate::{
+    board::Board,
+    lookup::pawn_attacks,
+    random::Random,
+    types::{CastlingKind, Color, Move, POLYGLOT, PieceType, Square},
+};
+
+const ENTRY_SIZE: usize = 16;
+
+#[derive(Copy, Clone)]
+struct Entry {
+    key: u64,
+    mv: u16,
+    weight: u16,
+}
+
+impl Entry {
+    fn from_bytes(bytes: &[u8]) -> Self {
+        Self {
+            key: u64::from_be_bytes(bytes[0..8].try_into().unwrap()),
+            mv: u16::from_be_bytes([bytes[8], bytes[9]]),
+            weight: u16::from_be_bytes([bytes[10], bytes[11]]),
+        }
+    }
+}
+
+/// A legal move of the position found in the book, together with its weight.
+#[derive(Copy, Clone)]
+pub struct BookMove {
+    pub mv: Move,
+    pub weight: u16,
+}
+
+pub struct Book {
+    entries: Vec<Entry>,
+}
+
+impl Book {
+    pub fn open(path: &str) -> std::io::Result<Self> {
+        let bytes = std::fs::read(path)?;
+
+        if bytes.is_empty() || bytes.len() % ENTRY_SIZE != 0 {
+            return Err(Error::new(ErrorKind::InvalidData, "not a Polyglot book"));
+        }
+
+        let entries = bytes.chunks_exact(ENTRY_SIZE).map(Entry::from_bytes).collect::<Vec<_>>();
+
+        if !entries.is_sorted_by_key(|entry| entry.key) {
+            return Err(Error::new(ErrorKind::InvalidData, "book entries are not sorted"));
+        }
+
+        Ok(Self { entries })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns the legal book moves for the position, ordered by decreasing weight.
+    pub fn moves(&self, board: &Board) -> Vec<BookMove> {
+        let key = key(board);
+        let start = self.entries.partition_point(|entry| entry.key < key);
+
+        let legal = board.generate_all_moves();
+        let mut moves = Vec::new();
+
+        for entry in self.entries[start..].iter().take_while(|entry| entry.key == key) {
+            if let Some(mv) = legal.iter().map(|entry| entry.mv).find(|&mv| encode(board, mv) == entry.mv)
+                && !moves.iter().any(|book: &BookMove| book.mv == mv)
+            {
+                moves.push(BookMove { mv, weight: entry.weight });
+            }
+        }
+
+        moves.sort_by_key(|book| std::cmp::Reverse(book.weight));
+        moves
+    }
+
+    /// Picks a book move, either the one with the highest weight or
+    /// a random one with probability proportional to its weight.
+    pub fn pick(&self, board: &Board, best: bool, rng: &mut Random) -> Option<Move> {
+        let moves = self.moves(board);
+        let total = moves.iter().map(|book| book.weight as u64).sum::<u64>();
+
+        if best || total == 0 {
+            return moves.first().map(|book| book.mv);
+        }
+
+        let mut choice = rng.below(total);
+        for book in &moves {
+            if choice < book.weight as u64 {
+                return Some(book.mv);
+            }
+            choice -= book.weight as u64;
+        }
+
+        None
+    }
+}
+
+/// Computes the Polyglot key of the position.
+pub fn key(board: &Board) -> u64 {
+    let mut key = 0;
+
+    for square in board.occupancies() {
+        let piece = board.piece_on(square);
+        let kind = 2 * piece.piece_type() as usize + (piece.color() == Color::White) as usize;
+        key ^= POLYGLOT.pieces[kind][square];
+    }
+
+    let kinds = [
+        CastlingKind::WhiteKingside,
+        CastlingKind::WhiteQueenside,
+        CastlingKind::BlackKingside,
+        CastlingKind::BlackQueenside,
+    ];
+
+    for (index, kind) in kinds.into_iter().enumerate() {
+        if board.castling().is_allowed(kind) {
+            key ^= POLYGLOT.castling[index];
+        }
+    }
+
+    // The en passant file is only hashed if a pawn is in position to make the capture
+    let stm = board.side_to_move();
+    if board.en_passant() != Square::None
+        && !(pawn_attacks(board.en_passant(), !stm) & board.colored_pieces(stm, PieceType::Pawn)).is_empty()
+    {
+        key ^= POLYGLOT.en_passant[board.en_passant().file() as usize];
+    }
+
+    if stm == Color::White {
+        key ^= POLYGLOT.side;
+    }
+
+    key
+}
+
+/// Encodes the move as stored in a Polyglot book, where castling is written as the king capturing its rook.
+fn encode(board: &Board, mv: Move) -> u16 {
+    let to = if mv.is_castling() { board.get_castling_rook(mv.to()).0 } else { mv.to() };
+
+    let promotion = match mv.is_promotion() {
+        true => match mv.promo_piece_type() {
+            PieceType::Knight => 1,
+            PieceType::Bishop => 2,
+            PieceType::Rook => 3,
+            _ => 4,
+        },
+        false => 0,
+    };
+
+    (to as u16) | ((mv.from() as u16) << 6) | (promotion << 12)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key() {
+        // Reference keys from the Polyglot book format specification
+        let cases = [
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 0x463b96181691fc9c),
+            ("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1", 0x823c9b50fd114196),
+            ("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2", 0x0756b94461c50fb0),
+            ("rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2", 0x662fafb965db29d4),
+            ("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3", 0x22a48b5a8e47ff78),
+            ("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR b kq - 0 3", 0x652a607ca3f242c1),
+            ("rnbq1bnr/ppp1pkpp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR w - - 0 4", 0x00fdd303c946bdd9),
+            ("rnbqkbnr/p1pppppp/8/8/PpP4P/8/1P1PPPP1/RNBQKBNR b KQkq c3 0 3", 0x3c8123ea7b067637),
+            ("rnbqkbnr/p1pppppp/8/8/P6P/R1p5/1P1PPPP1/1NBQKBNR b Kkq - 0 4", 0x5c3f9b829b279560),
+        ];
+
+        for (fen, expected) in cases {
+            assert_eq!(key(&Board::from_fen(fen).unwrap()), expected, "{fen}");
+        }
+    }
+}
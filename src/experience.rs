@@ -0,0 +1,183 @@
+//! Persistent store of deep root search results, keyed by the position hash.
+//!
+//! Whenever a search finishes deep enough, its best move and score are appended to
+//! the experience file. When the same position is searched again, possibly in a later
+//! session, the stored result is written into the transposition table before the search
+//! starts, so that the best move is tried first and the previous score is available.
+
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{BufReader, Error, ErrorKind, Read, Write},
+};
+
+use crate::{
+    board::Board,
+    transposition::{Bound, TranspositionTable},
+    types::{Move, Score},
+};
+
+pub const DEFAULT_EXPERIENCE_FILE: &str = "experience.bin";
+
+/// Minimum completed depth for a search result to be recorded.
+pub const MIN_DEPTH: i32 = 12;
+
+const FILE_MAGIC: [u8; 4] = *b"RKEX";
+const FILE_VERSION: u32 = 1;
+const FILE_HEADER_SIZE: usize = 8;
+const RECORD_SIZE: usize = 16;
+
+#[derive(Copy, Clone)]
+struct Record {
+    mv: u16,
+    score: i16,
+    depth: u8,
+}
+
+impl Record {
+    fn to_bytes(self, hash: u64) -> [u8; RECORD_SIZE] {
+        let mut bytes = [0; RECORD_SIZE];
+        bytes[0..8].copy_from_slice(&hash.to_le_bytes());
+        bytes[8..10].copy_from_slice(&self.mv.to_le_bytes());
+        bytes[10..12].copy_from_slice(&self.score.to_le_bytes());
+        bytes[12] = self.depth;
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8; RECORD_SIZE]) -> (u64, Self) {
+        let hash = u64::from_le_bytes(bytes[0..8].try_into().unwrap());
+        let mv = u16::from_le_bytes([bytes[8], bytes[9]]);
+        let score = i16::from_le_bytes([bytes[10], bytes[11]]);
+        (hash, Self { mv, score, depth: bytes[12] })
+    }
+}
+
+/// Append-only file of root results, of which the deepest result per position is kept in memory.
+pub struct Experience {
+    file: File,
+    records: HashMap<u64, Record>,
+}
+
+impl Experience {
+    /// Opens the experience file at `path`, creating it if it does not exist.
+    pub fn open(path: &str) -> std::io::Result<Self> {
+        let mut file = OpenOptions::new().read(true).append(true).create(true).open(path)?;
+        let mut records = HashMap::new();
+
+        if file.metadata()?.len() == 0 {
+            let mut header = [0; FILE_HEADER_SIZE];
+            header[0..4].copy_from_slice(&FILE_MAGIC);
+            header[4..8].copy_from_slice(&FILE_VERSION.to_le_bytes());
+            file.write_all(&header)?;
+            return Ok(Self { file, records });
+        }
+
+        let mut reader = BufReader::new(&mut file);
+
+        let mut header = [0; FILE_HEADER_SIZE];
+        reader.read_exact(&mut header)?;
+
+        if header[0..4] != FILE_MAGIC || header[4..8] != FILE_VERSION.to_le_bytes() {
+            return Err(Error::new(ErrorKind::InvalidData, "not an experience file"));
+        }
+
+        let mut bytes = [0; RECORD_SIZE];
+        loop {
+            match reader.read_exact(&mut bytes) {
+                Ok(()) => (),
+                Err(error) if error.kind() == ErrorKind::UnexpectedEof => break,
+                Err(error) => return Err(error),
+            }
+
+            let (hash, record) = Record::from_bytes(&bytes);
+            records.entry(hash).and_modify(|entry: &mut Record| *entry = deeper(*entry, record)).or_insert(record);
+        }
+
+        Ok(Self { file, records })
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Writes the stored result for `board` into the transposition table,
+    /// returning whether the position was found with a legal move.
+    pub fn inject(&self, board: &Board, tt: &TranspositionTable) -> bool {
+        let Some(record) = self.records.get(&board.hash()) else {
+            return false;
+        };
+
+        let Some(entry) = board.generate_all_moves().iter().find(|entry| entry.mv.raw() == record.mv).copied() else {
+            return false;
+        };
+
+        let score = record.score as i32;
+        tt.write(board.hash(), record.depth as i32, Score::NONE, score, Bound::Exact, entry.mv, 0, true, true);
+        true
+    }
+
+    /// Records the result of a finished search, unless a deeper result is already stored.
+    pub fn record(&mut self, board: &Board, mv: Move, score: i32, depth: i32) -> std::io::Result<()> {
+        if depth < MIN_DEPTH || score.abs() >= Score::INFINITE || mv.is_null() {
+            return Ok(());
+        }
+
+        let record = Record {
+            mv: mv.raw(),
+            score: score as i16,
+            depth: depth.min(u8::MAX as i32) as u8,
+        };
+
+        if self.records.get(&board.hash()).is_some_and(|entry| entry.depth >= record.depth) {
+            return Ok(());
+        }
+
+        self.file.write_all(&record.to_bytes(board.hash()))?;
+        self.records.insert(board.hash(), record);
+        Ok(())
+    }
+}
+
+const fn deeper(a: Record, b: Record) -> Record {
+    if b.depth >= a.depth { b } else { a }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find_move(board: &Board, uci: &str) -> Move {
+        board.generate_all_moves().iter().map(|entry| entry.mv).find(|mv| mv.to_uci(board) == uci).unwrap()
+    }
+
+    #[test]
+    fn test_inject() {
+        let path = std::env::temp_dir().join(format!("reckless-experience-{}.bin", std::process::id()));
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+
+        let board = Board::starting_position();
+        let other = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+
+        let mut experience = Experience::open(path).unwrap();
+        experience.record(&board, find_move(&board, "a2a3"), 35, MIN_DEPTH + 4).unwrap();
+        experience.record(&board, find_move(&board, "h2h3"), -20, MIN_DEPTH).unwrap();
+        experience.record(&other, find_move(&other, "e2a6"), 10, MIN_DEPTH - 1).unwrap();
+        drop(experience);
+
+        // The deepest result survives reopening, while shallow searches are not recorded
+        let experience = Experience::open(path).unwrap();
+        assert_eq!(experience.len(), 1);
+
+        let tt = TranspositionTable::default();
+        assert!(!experience.inject(&other, &tt));
+        assert!(experience.inject(&board, &tt));
+
+        let entry = tt.read(board.hash(), board.fiftymove_clock(), 0).unwrap();
+        assert_eq!(entry.mv, find_move(&board, "a2a3"));
+        assert_eq!((entry.score, entry.depth), (35, MIN_DEPTH + 4));
+        assert!(entry.bound == Bound::Exact && entry.tt_pv);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
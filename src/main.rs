@@ -4,7 +4,9 @@
 #![warn(clippy::redundant_clone)]
 
 mod board;
+mod book;
 mod evaluation;
+mod experience;
 mod history;
 mod lookup;
 mod misc;
@@ -12,8 +14,11 @@ mod movepick;
 mod nnue;
 mod numa;
 mod parameters;
+mod pgn;
+mod random;
 mod search;
 mod setwise;
+mod skill;
 mod stack;
 mod thread;
 mod threadpool;
@@ -26,6 +31,9 @@ mod uci;
 #[cfg(feature = "syzygy")]
 mod tb;
 
+#[cfg(feature = "trace")]
+mod trace;
+
 #[cfg(feature = "syzygy")]
 #[allow(warnings)]
 mod bindings;
@@ -34,7 +42,11 @@ fn main() {
     lookup::initialize();
     nnue::initialize();
 
-    let buffer: std::collections::VecDeque<String> = std::env::args().skip(1).collect();
+    let mut buffer: std::collections::VecDeque<String> = std::env::args().skip(1).collect();
+
+    // Emit search info as JSON objects instead of UCI `info` lines
+    let json_output = buffer.iter().any(|arg| arg == "--json");
+    buffer.retain(|arg| arg != "--json");
 
-    uci::message_loop(buffer);
+    uci::message_loop(buffer, json_output);
 }
@@ -9,9 +9,12 @@ use crate::{
 #[derive(Copy, Clone, Eq, PartialEq, PartialOrd)]
 pub enum Stage {
     HashMove,
+    GenerateEvasions,
+    Evasions,
     GenerateNoisy,
     GoodNoisy,
     Quiet,
+    QuietChecks,
     BadNoisy,
 }
 
@@ -23,6 +26,7 @@ pub struct MovePicker {
     bad_noisy: ArrayVec<Move, MAX_MOVES>,
     bad_noisy_idx: usize,
     noisy_count: usize,
+    quiet_checks: bool,
 }
 
 impl MovePicker {
@@ -35,6 +39,17 @@ impl MovePicker {
             bad_noisy: ArrayVec::new(),
             bad_noisy_idx: 0,
             noisy_count: 0,
+            quiet_checks: false,
+        }
+    }
+
+    /// Creates a move picker for quiescence search. In check, every evasion is yielded, captures
+    /// first; otherwise the noisy moves, followed by the quiet checks when `checks` is set.
+    pub const fn new_qsearch(in_check: bool, checks: bool) -> Self {
+        Self {
+            stage: if in_check { Stage::GenerateEvasions } else { Stage::GenerateNoisy },
+            quiet_checks: checks && !in_check,
+            ..Self::new(Move::NULL, None)
         }
     }
 
@@ -51,6 +66,22 @@ impl MovePicker {
             }
         }
 
+        if self.stage == Stage::GenerateEvasions {
+            self.stage = Stage::Evasions;
+            td.board.append_evasions(&mut self.list);
+            self.remove_tt();
+            self.score_evasions(td, ply);
+        }
+
+        if self.stage == Stage::Evasions {
+            if self.list.is_empty() {
+                return None;
+            }
+
+            let entry = self.get_best_entry();
+            return if skip_quiets && entry.mv.is_quiet() { None } else { Some(entry.mv) };
+        }
+
         if self.stage == Stage::GenerateNoisy {
             self.stage = Stage::GoodNoisy;
             td.board.append_noisy_moves(&mut self.list);
@@ -75,7 +106,11 @@ impl MovePicker {
                 return Some(entry.mv);
             }
 
-            if skip_quiets {
+            if skip_quiets && self.quiet_checks {
+                self.stage = Stage::QuietChecks;
+                td.board.append_quiet_checks(&mut self.list);
+                self.score_quiet(td, ply);
+            } else if skip_quiets {
                 self.stage = Stage::BadNoisy;
             } else {
                 self.stage = Stage::Quiet;
@@ -96,6 +131,14 @@ impl MovePicker {
             self.stage = Stage::BadNoisy;
         }
 
+        if self.stage == Stage::QuietChecks {
+            if !self.list.is_empty() {
+                return Some(self.get_best_entry().mv);
+            }
+
+            self.stage = Stage::BadNoisy;
+        }
+
         // Stage::BadNoisy
         if self.bad_noisy_idx < self.bad_noisy.len() {
             let mv = self.bad_noisy[self.bad_noisy_idx];
@@ -140,6 +183,23 @@ impl MovePicker {
         }
     }
 
+    fn score_evasions(&mut self, td: &ThreadData, ply: isize) {
+        let threats = td.board.all_threats();
+        let side = td.board.side_to_move();
+
+        for entry in self.list.iter_mut() {
+            let mv = entry.mv;
+
+            entry.score = if mv.is_noisy() {
+                // Most valuable victim first, ahead of every quiet evasion
+                let captured = td.board.type_on(mv.capture_sq());
+                (1 << 24) + 16 * captured.value() - td.board.type_on(mv.from()) as i32
+            } else {
+                td.quiet_history.get(threats, side, mv) + td.conthist(ply, 1, mv)
+            };
+        }
+    }
+
     fn score_quiet(&mut self, td: &ThreadData, ply: isize) {
         let threats = td.board.all_threats();
         let side = td.board.side_to_move();
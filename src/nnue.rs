@@ -1,8 +1,10 @@
 mod accumulator;
+mod header;
 
 pub use accumulator::threats::initialize;
+pub use header::Header;
 
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 
 use crate::{
     board::{Board, BoardObserver},
@@ -53,6 +55,8 @@ const NETWORK_SCALE: i32 = 380;
 const INPUT_BUCKETS: usize = 10;
 const OUTPUT_BUCKETS: usize = 8;
 
+const THREAT_FEATURES: usize = 66864;
+
 const L1_SIZE: usize = 768;
 const L2_SIZE: usize = 16;
 const L3_SIZE: usize = 32;
@@ -324,7 +328,7 @@ impl BoardObserver for Network {
 
 #[repr(C)]
 pub struct Parameters {
-    ft_threat_weights: Aligned<[[i8; L1_SIZE]; 66864]>,
+    ft_threat_weights: Aligned<[[i8; L1_SIZE]; THREAT_FEATURES]>,
     ft_piece_weights: Aligned<[[i16; L1_SIZE]; INPUT_BUCKETS * 768]>,
     ft_biases: Aligned<[i16; L1_SIZE]>,
     l1_weights: Aligned<[[i8; L2_SIZE * L1_SIZE]; OUTPUT_BUCKETS]>,
@@ -335,19 +339,166 @@ pub struct Parameters {
     l3_biases: Aligned<[f32; OUTPUT_BUCKETS]>,
 }
 
+/// Network loaded through the `EvalFile` option. When unset, the embedded network is used.
+static EXTERNAL: RwLock<Option<External>> = RwLock::new(None);
+
+struct External {
+    path: String,
+    header: Header,
+    parameters: Arc<Parameters>,
+}
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(std::io::Error),
+    InvalidSize { expected: usize, found: usize },
+    MissingHeader,
+    TruncatedHeader,
+    NonZeroReserved,
+    UnsupportedVersion(u32),
+    ArchitectureMismatch { field: &'static str, expected: u32, found: u32 },
+    HashMismatch { expected: u64, found: u64 },
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{error}"),
+            Self::InvalidSize { expected, found } => {
+                write!(f, "invalid network size: expected {expected} bytes, found {found}")
+            }
+            Self::MissingHeader => write!(f, "missing network header"),
+            Self::TruncatedHeader => write!(f, "truncated network header"),
+            Self::NonZeroReserved => write!(f, "non-zero reserved bytes in network header"),
+            Self::UnsupportedVersion(version) => write!(f, "unsupported network version {version}"),
+            Self::ArchitectureMismatch { field, expected, found } => {
+                write!(f, "architecture mismatch in {field}: expected {expected}, found {found}")
+            }
+            Self::HashMismatch { expected, found } => {
+                write!(f, "hash mismatch: header says {expected:016x}, parameters hash to {found:016x}")
+            }
+        }
+    }
+}
+
+/// Replaces the active network with the one stored at `path`, or restores the embedded
+/// network if `path` is `None`. Existing replicas are not affected until they are re-allocated.
+///
+/// The file must start with a [`Header`] matching the architecture of this binary and the
+/// hash of the parameters that follow it.
+pub fn load(path: Option<&str>) -> Result<(), LoadError> {
+    let external = match path {
+        Some(path) => {
+            let bytes = std::fs::read(path).map_err(LoadError::Io)?;
+            let (header, parameters) = Parameters::from_file(&bytes)?;
+            Some(External { path: path.to_string(), header, parameters })
+        }
+        None => None,
+    };
+
+    *EXTERNAL.write().unwrap() = external;
+    Ok(())
+}
+
+/// Writes the headerless network at `input`, such as a released or freshly trained one, to
+/// `output` preceded by the [`Header`] of this binary's architecture, so that it can be
+/// loaded through `EvalFile`.
+pub fn add_header(input: &str, output: &str) -> Result<Header, LoadError> {
+    let bytes = std::fs::read(input).map_err(LoadError::Io)?;
+    let header = Header::current(Parameters::from_bytes(&bytes)?.hash());
+
+    let mut file = header.to_bytes().to_vec();
+    file.extend_from_slice(&bytes);
+
+    std::fs::write(output, file).map_err(LoadError::Io)?;
+    Ok(header)
+}
+
+/// Returns the header of the active network and the file it was loaded from, if any.
+pub fn info() -> (Header, Option<String>) {
+    match EXTERNAL.read().unwrap().as_ref() {
+        Some(external) => (external.header, Some(external.path.clone())),
+        None => (Header::current(Parameters::embedded().hash()), None),
+    }
+}
+
 impl Parameters {
     fn embedded() -> &'static Self {
         static EMBEDDED: Parameters = unsafe { std::mem::transmute(*include_bytes!(env!("MODEL"))) };
         &EMBEDDED
     }
 
+    fn external() -> Option<Arc<Self>> {
+        EXTERNAL.read().unwrap().as_ref().map(|external| external.parameters.clone())
+    }
+
+    fn from_file(bytes: &[u8]) -> Result<(Header, Arc<Self>), LoadError> {
+        let header = Header::parse(bytes).ok_or(LoadError::MissingHeader)??;
+        header.validate()?;
+
+        let parameters = Self::from_bytes(&bytes[header::HEADER_SIZE..])?;
+        let hash = parameters.hash();
+
+        if hash != header.hash {
+            return Err(LoadError::HashMismatch { expected: header.hash, found: hash });
+        }
+
+        Ok((header, parameters))
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Arc<Self>, LoadError> {
+        if bytes.len() != std::mem::size_of::<Self>() {
+            return Err(LoadError::InvalidSize { expected: std::mem::size_of::<Self>(), found: bytes.len() });
+        }
+
+        // SAFETY: every field is a plain array of integers or floats, so any bit pattern is valid.
+        unsafe { Ok(Self::allocate_from(bytes.as_ptr().cast())) }
+    }
+
+    /// Hashes the parameter values, skipping the alignment padding between fields.
+    fn hash(&self) -> u64 {
+        self.fields().into_iter().fold(header::FNV_OFFSET, header::fnv1a)
+    }
+
+    /// Returns the raw bytes of every field.
+    fn fields(&self) -> [&[u8]; 9] {
+        fn bytes<T>(data: &Aligned<T>) -> &[u8] {
+            unsafe { std::slice::from_raw_parts((&data.data as *const T).cast::<u8>(), std::mem::size_of::<T>()) }
+        }
+
+        macro_rules! fields {
+            ($($field:ident),*) => {
+                [$(bytes(&self.$field)),*]
+            };
+        }
+
+        fields!(
+            ft_threat_weights,
+            ft_piece_weights,
+            ft_biases,
+            l1_weights,
+            l1_biases,
+            l2_weights,
+            l2_biases,
+            l3_weights,
+            l3_biases
+        )
+    }
+
     fn allocate_owned() -> Arc<Self> {
+        match Self::external() {
+            Some(external) => unsafe { Self::allocate_from(Arc::as_ptr(&external)) },
+            None => unsafe { Self::allocate_from(Self::embedded()) },
+        }
+    }
+
+    unsafe fn allocate_from(source: *const Self) -> Arc<Self> {
         let mut boxed = Box::<std::mem::MaybeUninit<Self>>::new(std::mem::MaybeUninit::uninit());
         let ptr = boxed.as_mut_ptr();
         std::mem::forget(boxed);
 
         unsafe {
-            std::ptr::copy_nonoverlapping(Self::embedded() as *const Self, ptr, 1);
+            std::ptr::copy_nonoverlapping(source.cast::<u8>(), ptr.cast::<u8>(), std::mem::size_of::<Self>());
             Arc::from(Box::from_raw(ptr))
         }
     }
@@ -391,7 +542,10 @@ impl NumaReplicable for ParametersHandle {
     }
 
     fn allocate_shared() -> Option<Arc<Self>> {
-        Arc::new(Self::embedded()).into()
+        match Parameters::external() {
+            Some(external) => Arc::new(Self::owned(external)).into(),
+            None => Arc::new(Self::embedded()).into(),
+        }
     }
 }
 
@@ -420,3 +574,35 @@ impl<T> std::ops::DerefMut for Aligned<T> {
         &mut self.data
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_header() {
+        let dir = std::env::temp_dir();
+        let raw = dir.join(format!("reckless-raw-{}.nnue", std::process::id()));
+        let output = dir.join(format!("reckless-header-{}.nnue", std::process::id()));
+        let (raw, output) = (raw.to_str().unwrap(), output.to_str().unwrap());
+
+        let embedded = Parameters::embedded();
+        let bytes = unsafe {
+            std::slice::from_raw_parts((embedded as *const Parameters).cast::<u8>(), std::mem::size_of::<Parameters>())
+        };
+        std::fs::write(raw, bytes).unwrap();
+
+        let header = add_header(raw, output).unwrap();
+        assert_eq!(header, Header::current(embedded.hash()));
+
+        let file = std::fs::read(output).unwrap();
+        assert_eq!(Parameters::from_file(&file).unwrap().0, header);
+        assert!(matches!(Parameters::from_file(bytes), Err(LoadError::MissingHeader)));
+
+        // A network that is already headed is not a valid raw network
+        assert!(matches!(add_header(output, raw), Err(LoadError::InvalidSize { .. })));
+
+        std::fs::remove_file(raw).unwrap();
+        std::fs::remove_file(output).unwrap();
+    }
+}
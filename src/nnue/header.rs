@@ -0,0 +1,206 @@
+use super::{
+    FT_QUANT, INPUT_BUCKETS, L1_QUANT, L1_SIZE, L2_SIZE, L3_SIZE, LoadError, NETWORK_SCALE, OUTPUT_BUCKETS,
+    THREAT_FEATURES,
+};
+
+pub const MAGIC: [u8; 4] = *b"RKNN";
+pub const VERSION: u32 = 1;
+
+/// Size of the serialized header. Unused trailing bytes are reserved and must be zero.
+pub const HEADER_SIZE: usize = 64;
+
+/// Offset of the reserved bytes that end the header.
+const RESERVED: usize = 52;
+
+/// Header preceding the raw network parameters in an external network file.
+///
+/// It records the architecture the network was trained for, so that a mismatching
+/// network is rejected instead of being silently misinterpreted.
+///
+/// The layout of the 64 bytes is as follows (all integers are little-endian):
+///
+/// | Offset | Size | Contents                                                     |
+/// | ------ | ---- | ------------------------------------------------------------ |
+/// | 0      | 4    | Magic `RKNN`                                                 |
+/// | 4      | 4    | Format version, currently 1                                  |
+/// | 8      | 4    | Input buckets                                                |
+/// | 12     | 4    | Output buckets                                               |
+/// | 16     | 4    | Threat features                                              |
+/// | 20     | 4    | L1 size                                                      |
+/// | 24     | 4    | L2 size                                                      |
+/// | 28     | 4    | L3 size                                                      |
+/// | 32     | 4    | Feature transformer quantization                             |
+/// | 36     | 4    | L1 quantization                                              |
+/// | 40     | 4    | Network scale                                                |
+/// | 44     | 8    | FNV-1a hash of the parameters, excluding alignment padding   |
+/// | 52     | 12   | Reserved, must be zero                                       |
+///
+/// The raw parameters follow the header, in the same layout as the embedded network.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Header {
+    pub version: u32,
+    pub input_buckets: u32,
+    pub output_buckets: u32,
+    pub threat_features: u32,
+    pub l1_size: u32,
+    pub l2_size: u32,
+    pub l3_size: u32,
+    pub ft_quant: u32,
+    pub l1_quant: u32,
+    pub network_scale: u32,
+    pub hash: u64,
+}
+
+impl Header {
+    /// Describes the architecture of this binary for a network with the given hash.
+    pub const fn current(hash: u64) -> Self {
+        Self {
+            version: VERSION,
+            input_buckets: INPUT_BUCKETS as u32,
+            output_buckets: OUTPUT_BUCKETS as u32,
+            threat_features: THREAT_FEATURES as u32,
+            l1_size: L1_SIZE as u32,
+            l2_size: L2_SIZE as u32,
+            l3_size: L3_SIZE as u32,
+            ft_quant: FT_QUANT as u32,
+            l1_quant: L1_QUANT as u32,
+            network_scale: NETWORK_SCALE as u32,
+            hash,
+        }
+    }
+
+    /// Parses the header at the start of `bytes`, returning `None` if it does not start with [`MAGIC`].
+    pub fn parse(bytes: &[u8]) -> Option<Result<Self, LoadError>> {
+        if !bytes.starts_with(&MAGIC) {
+            return None;
+        }
+
+        if bytes.len() < HEADER_SIZE {
+            return Some(Err(LoadError::TruncatedHeader));
+        }
+
+        let u32_at = |index: usize| {
+            let offset = MAGIC.len() + 4 * index;
+            u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+        };
+
+        let version = u32_at(0);
+        if version != VERSION {
+            return Some(Err(LoadError::UnsupportedVersion(version)));
+        }
+
+        if bytes[RESERVED..HEADER_SIZE].iter().any(|&byte| byte != 0) {
+            return Some(Err(LoadError::NonZeroReserved));
+        }
+
+        Some(Ok(Self {
+            version,
+            input_buckets: u32_at(1),
+            output_buckets: u32_at(2),
+            threat_features: u32_at(3),
+            l1_size: u32_at(4),
+            l2_size: u32_at(5),
+            l3_size: u32_at(6),
+            ft_quant: u32_at(7),
+            l1_quant: u32_at(8),
+            network_scale: u32_at(9),
+            hash: u64::from_le_bytes(bytes[44..RESERVED].try_into().unwrap()),
+        }))
+    }
+
+    pub fn to_bytes(self) -> [u8; HEADER_SIZE] {
+        let mut bytes = [0; HEADER_SIZE];
+        bytes[..4].copy_from_slice(&MAGIC);
+
+        let fields = [
+            self.version,
+            self.input_buckets,
+            self.output_buckets,
+            self.threat_features,
+            self.l1_size,
+            self.l2_size,
+            self.l3_size,
+            self.ft_quant,
+            self.l1_quant,
+            self.network_scale,
+        ];
+
+        for (index, field) in fields.iter().enumerate() {
+            let offset = MAGIC.len() + 4 * index;
+            bytes[offset..offset + 4].copy_from_slice(&field.to_le_bytes());
+        }
+
+        bytes[44..RESERVED].copy_from_slice(&self.hash.to_le_bytes());
+        bytes
+    }
+
+    /// Checks that the network described by this header can be used by this binary.
+    pub fn validate(&self) -> Result<(), LoadError> {
+        let expected = Self::current(self.hash);
+
+        let fields = [
+            ("input buckets", expected.input_buckets, self.input_buckets),
+            ("output buckets", expected.output_buckets, self.output_buckets),
+            ("threat features", expected.threat_features, self.threat_features),
+            ("L1 size", expected.l1_size, self.l1_size),
+            ("L2 size", expected.l2_size, self.l2_size),
+            ("L3 size", expected.l3_size, self.l3_size),
+            ("FT quantization", expected.ft_quant, self.ft_quant),
+            ("L1 quantization", expected.l1_quant, self.l1_quant),
+            ("network scale", expected.network_scale, self.network_scale),
+        ];
+
+        match fields.into_iter().find(|(_, expected, found)| expected != found) {
+            Some((field, expected, found)) => Err(LoadError::ArchitectureMismatch { field, expected, found }),
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::fmt::Display for Header {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Version          {}", self.version)?;
+        writeln!(f, "Input buckets    {}", self.input_buckets)?;
+        writeln!(f, "Output buckets   {}", self.output_buckets)?;
+        writeln!(f, "Threat features  {}", self.threat_features)?;
+        writeln!(f, "Layers           {} -> {} -> {} -> 1", self.l1_size, self.l2_size, self.l3_size)?;
+        writeln!(f, "Quantization     FT {}, L1 {}", self.ft_quant, self.l1_quant)?;
+        writeln!(f, "Network scale    {}", self.network_scale)?;
+        write!(f, "Hash             {:016x}", self.hash)
+    }
+}
+
+/// 64-bit FNV-1a hash, used to identify the network parameters.
+pub fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
+pub const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_round_trip() {
+        let header = Header::current(0x0123_4567_89ab_cdef);
+        let bytes = header.to_bytes();
+
+        assert_eq!(Header::parse(&bytes).unwrap().unwrap(), header);
+        assert!(header.validate().is_ok());
+
+        assert!(Header::parse(&[0; HEADER_SIZE]).is_none());
+        assert!(matches!(Header::parse(&bytes[..HEADER_SIZE - 1]), Some(Err(LoadError::TruncatedHeader))));
+
+        let mut bytes = bytes;
+        bytes[HEADER_SIZE - 1] = 1;
+        assert!(matches!(Header::parse(&bytes), Some(Err(LoadError::NonZeroReserved))));
+
+        bytes[HEADER_SIZE - 1] = 0;
+        bytes[4..8].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        assert!(matches!(Header::parse(&bytes), Some(Err(LoadError::UnsupportedVersion(_)))));
+
+        let header = Header { l1_size: header.l1_size + 1, ..header };
+        assert!(matches!(header.validate(), Err(LoadError::ArchitectureMismatch { field: "L1 size", .. })));
+    }
+}
@@ -339,6 +339,11 @@ impl<T: NumaReplicable> NumaReplicated<T> {
         self.instances.read().unwrap().clone()
     }
 
+    /// Re-allocates every replica, picking up changes to the data they are created from.
+    pub fn reload(&self) {
+        self.replicate_instances();
+    }
+
     fn replicate_instances(&self) {
         let cfg = self.ctx.get_numa_config();
         let mut instances = Vec::<Arc<T>>::new();
@@ -0,0 +1,235 @@
+//! Reader for games stored in [Portable Game Notation][pgn].
+//!
+//! Tag pairs and the main line are kept, while comments, variations and
+//! numeric annotation glyphs are skipped.
+//!
+//! [pgn]: https://www.chessprogramming.org/Portable_Game_Notation
+
+use crate::{
+    board::{Board, NullBoardObserver},
+    types::Move,
+};
+
+#[derive(Debug)]
+pub enum PgnError {
+    /// The `FEN` tag of the game does not describe a valid position.
+    InvalidFen(String),
+    /// A move of the main line is not legal in its position.
+    IllegalMove { ply: usize, san: String },
+    /// A comment or tag pair is not closed before the end of the input.
+    Unterminated(char),
+    /// A variation is closed without being opened.
+    UnbalancedVariation,
+}
+
+impl std::fmt::Display for PgnError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidFen(fen) => write!(f, "invalid FEN tag '{fen}'"),
+            Self::IllegalMove { ply, san } => write!(f, "illegal move '{san}' at ply {ply}"),
+            Self::Unterminated(c) => write!(f, "unterminated '{c}'"),
+            Self::UnbalancedVariation => write!(f, "unbalanced variation"),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Game {
+    pub tags: Vec<(String, String)>,
+    pub start: Board,
+    pub moves: Vec<Move>,
+    pub result: String,
+}
+
+impl Game {
+    fn new(tags: Vec<(String, String)>, frc: bool) -> Result<Self, PgnError> {
+        let mut start = match tags.iter().find(|(name, _)| name == "FEN") {
+            Some((_, fen)) => Board::from_fen(fen).map_err(|_| PgnError::InvalidFen(fen.clone()))?,
+            None => Board::starting_position(),
+        };
+
+        let variant = tags.iter().any(|(name, value)| name == "Variant" && value.to_lowercase().contains("960"));
+        start.set_frc(frc || variant);
+
+        Ok(Self { tags, start, moves: Vec::new(), result: "*".to_string() })
+    }
+
+    /// Returns the position after the first `ply` moves of the game.
+    pub fn board_at(&self, ply: usize) -> Board {
+        let mut board = self.start.clone();
+        for &mv in self.moves.iter().take(ply) {
+            board.make_move(mv, &mut NullBoardObserver);
+        }
+        board
+    }
+}
+
+/// Parses every game in `text`.
+///
+/// Castling moves are read as Chess960 castling if `frc` is set or the `Variant` tag of the game says so.
+pub fn parse(text: &str, frc: bool) -> Result<Vec<Game>, PgnError> {
+    let mut games = Vec::new();
+    let mut tags = Vec::new();
+    let mut game: Option<(Game, Board)> = None;
+    let mut depth = 0usize;
+
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        match c {
+            _ if c.is_whitespace() => (),
+            '[' if depth == 0 => {
+                if let Some((game, _)) = game.take() {
+                    games.push(game);
+                }
+
+                let end = tag_end(&text[start..]).ok_or(PgnError::Unterminated('['))? + start;
+                tags.extend(parse_tag(&text[start + 1..end]));
+                while chars.next_if(|&(index, _)| index <= end).is_some() {}
+            }
+            '{' => {
+                if chars.find(|&(_, c)| c == '}').is_none() {
+                    return Err(PgnError::Unterminated('{'));
+                }
+            }
+            ';' => while chars.next_if(|&(_, c)| c != '\n').is_some() {},
+            '(' => depth += 1,
+            ')' => depth = depth.checked_sub(1).ok_or(PgnError::UnbalancedVariation)?,
+            _ => {
+                while chars.next_if(|&(_, c)| !c.is_whitespace() && !"{}()[];".contains(c)).is_some() {}
+                let end = chars.peek().map_or(text.len(), |&(index, _)| index);
+                let token = &text[start..end];
+
+                if depth > 0 || token.starts_with('$') {
+                    continue;
+                }
+
+                if game.is_none() {
+                    let new = Game::new(std::mem::take(&mut tags), frc)?;
+                    let board = new.start.clone();
+                    game = Some((new, board));
+                }
+
+                let (current, board) = game.as_mut().unwrap();
+
+                if matches!(token, "1-0" | "0-1" | "1/2-1/2" | "*") {
+                    current.result = token.to_string();
+                    games.push(game.take().unwrap().0);
+                    continue;
+                }
+
+                let san = token.trim_start_matches(|c: char| c.is_ascii_digit()).trim_start_matches('.');
+                if san.is_empty() {
+                    continue;
+                }
+
+                let ply = current.moves.len() + 1;
+                let mv = board.parse_san(san).ok_or_else(|| PgnError::IllegalMove { ply, san: san.to_string() })?;
+
+                board.make_move(mv, &mut NullBoardObserver);
+                current.moves.push(mv);
+            }
+        }
+    }
+
+    if let Some((game, _)) = game {
+        games.push(game);
+    }
+
+    Ok(games)
+}
+
+/// Returns the index of the `]` closing the tag pair at the start of `text`, skipping any
+/// inside its quoted value.
+fn tag_end(text: &str) -> Option<usize> {
+    let mut quoted = false;
+    let mut escaped = false;
+
+    for (index, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            ']' if !quoted => return Some(index),
+            _ => (),
+        }
+    }
+    None
+}
+
+/// Parses the contents of a tag pair, such as `Event "Casual game"`.
+fn parse_tag(tag: &str) -> Option<(String, String)> {
+    let (name, value) = tag.trim().split_once(char::is_whitespace)?;
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+    Some((name.to_string(), value.replace("\\\"", "\"").replace("\\\\", "\\")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sans(game: &Game) -> Vec<String> {
+        let mut board = game.start.clone();
+        let mut sans = Vec::new();
+        for &mv in &game.moves {
+            sans.push(mv.to_san(&board));
+            board.make_move(mv, &mut NullBoardObserver);
+        }
+        sans
+    }
+
+    #[test]
+    fn test_tags() {
+        let text = r#"[Event "Rated [blitz] game"]
+[White "A \"B\" C"]
+[Site "C:\\games"]
+
+1. e4 *"#;
+        let games = parse(text, false).unwrap();
+
+        assert_eq!(games.len(), 1);
+        assert_eq!(
+            games[0].tags,
+            [
+                ("Event".to_string(), "Rated [blitz] game".to_string()),
+                ("White".to_string(), "A \"B\" C".to_string()),
+                ("Site".to_string(), "C:\\games".to_string()),
+            ]
+        );
+        assert!(matches!(parse("[Event \"x]", false), Err(PgnError::Unterminated('['))));
+    }
+
+    #[test]
+    fn test_comments_variations_and_nags() {
+        let text = "1. e4 {best by test; (really)} e5 $1 2. Nf3 (2. f4 exf4 (2... d5) 3. Nf3) 2... Nc6 ; rest of line (\n3. Bb5! a6?! 1-0";
+        let games = parse(text, false).unwrap();
+
+        assert_eq!(sans(&games[0]), ["e4", "e5", "Nf3", "Nc6", "Bb5", "a6"]);
+        assert_eq!(games[0].result, "1-0");
+
+        assert!(matches!(parse("1. e4 { open", false), Err(PgnError::Unterminated('{'))));
+        assert!(matches!(parse("1. e4 ) e5", false), Err(PgnError::UnbalancedVariation)));
+        assert!(matches!(parse("1. e4 e4", false), Err(PgnError::IllegalMove { ply: 2, .. })));
+    }
+
+    #[test]
+    fn test_results() {
+        let text = "1. d4 d5 1/2-1/2\n\n[FEN \"4k3/8/8/8/8/8/8/4K2R w K - 0 1\"]\n\n1. O-O 0-1\n\n1. c4 *\n\n1. e4";
+        let games = parse(text, false).unwrap();
+
+        let results = games.iter().map(|game| game.result.as_str()).collect::<Vec<_>>();
+        assert_eq!(results, ["1/2-1/2", "0-1", "*", "*"]);
+        assert_eq!(sans(&games[1]), ["O-O"]);
+        assert_eq!(games[3].moves.len(), 1);
+    }
+
+    #[test]
+    fn test_chess960() {
+        let text = "[FEN \"1r2k2r/8/8/8/8/8/8/R3K1R1 w GAhb - 0 1\"]\n\n1. O-O *";
+
+        assert_eq!(parse(text, true).unwrap()[0].board_at(1).to_fen(), "1r2k2r/8/8/8/8/8/8/R4RK1 b hb - 1 1");
+
+        let tagged = format!("[Variant \"Chess960\"]\n{text}");
+        assert_eq!(parse(&tagged, false).unwrap()[0].board_at(1).to_fen(), "1r2k2r/8/8/8/8/8/8/R4RK1 b hb - 1 1");
+    }
+}
@@ -0,0 +1,30 @@
+/// A small pseudorandom number generator based on SplitMix64, the same generator
+/// used to produce the Zobrist keys.
+///
+/// It is not suitable for cryptographic purposes, but the sequence is fully
+/// reproducible for a given seed, which is what the engine needs.
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub const fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub const fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a uniformly distributed number in the range `[0, bound)`.
+    pub const fn below(&mut self, bound: u64) -> u64 {
+        debug_assert!(bound > 0);
+
+        ((self.next_u64() as u128 * bound as u128) >> 64) as u64
+    }
+}
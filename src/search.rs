@@ -5,7 +5,6 @@ use crate::{
     movepick::{MovePicker, Stage},
     stack::Stack,
     thread::{PlyArray, RootMove, Status, ThreadData},
-    time::Limits,
     transposition::{Bound, TtDepth},
     types::{
         ArrayVec, Color, MAX_PLY, Move, Piece, PieceType, Score, Square, draw, is_decisive, is_loss, is_valid, is_win,
@@ -22,6 +21,30 @@ use crate::{
 #[allow(unused_imports)]
 use crate::misc::{dbg_hit, dbg_stats};
 
+/// Forwards a call to the search tracer of the thread, compiling to nothing unless
+/// the `trace` feature is enabled.
+macro_rules! trace {
+    ($td:expr, $method:ident($($arg:expr),*)) => {
+        #[cfg(feature = "trace")]
+        $td.trace.$method($($arg),*);
+    };
+}
+
+/// Counts a search event for the `debug on` diagnostics, optionally only if a condition
+/// holds, compiling to nothing unless the `stats` feature is enabled.
+macro_rules! record {
+    ($td:expr, $stat:ident) => {
+        #[cfg(feature = "stats")]
+        $td.record(crate::thread::Stat::$stat);
+    };
+    ($td:expr, $stat:ident, $condition:expr) => {
+        #[cfg(feature = "stats")]
+        if $condition {
+            $td.record(crate::thread::Stat::$stat);
+        }
+    };
+}
+
 #[derive(Copy, Clone, Eq, PartialEq)]
 pub enum Report {
     None,
@@ -54,6 +77,10 @@ impl NodeType for NonPV {
 
 pub fn start(td: &mut ThreadData, report: Report, thread_count: usize) {
     td.completed_depth = 0;
+    td.report = report;
+
+    #[cfg(feature = "trace")]
+    td.trace.clear();
 
     td.pv_table.clear(0);
     td.nnue.full_refresh(&td.board);
@@ -67,6 +94,11 @@ pub fn start(td: &mut ThreadData, report: Report, thread_count: usize) {
     let mut pv_stability = 0;
     let mut soft_stop_voted = false;
 
+    #[cfg(feature = "stats")]
+    let mut previous_nodes = 0;
+    #[cfg(feature = "stats")]
+    let mut previous_iteration_nodes = 0;
+
     if td.root_moves.is_empty() {
         if report == Report::Full {
             td.print_uci_info(0);
@@ -77,7 +109,7 @@ pub fn start(td: &mut ThreadData, report: Report, thread_count: usize) {
     // Iterative Deepening
     for depth in 1..MAX_PLY as i32 {
         if td.id == 0
-            && let Limits::Depth(maximum) = td.time_manager.limits()
+            && let Some(maximum) = td.time_manager.limits().depth
             && depth > maximum
         {
             td.shared.status.set(Status::STOPPED);
@@ -226,6 +258,27 @@ pub fn start(td: &mut ThreadData, report: Report, thread_count: usize) {
             td.print_uci_info(depth);
         }
 
+        #[cfg(feature = "stats")]
+        if report == Report::Full && td.shared.debug.load(Ordering::Relaxed) {
+            let nodes = td.shared.nodes.aggregate();
+            let iteration_nodes = nodes - previous_nodes;
+
+            td.print_debug_info(depth, iteration_nodes as f64 / previous_iteration_nodes.max(1) as f64);
+
+            previous_nodes = nodes;
+            previous_iteration_nodes = iteration_nodes;
+        }
+
+        // Stop as soon as a mate within the requested number of moves is proven
+        if td.id == 0
+            && let Some(moves) = td.time_manager.limits().mate
+            && td.root_moves[0].score >= mate_in(2 * moves as isize - 1)
+            && !td.root_moves[0].upperbound
+            && !td.root_moves[0].lowerbound
+        {
+            td.shared.status.set(Status::STOPPED);
+        }
+
         if td.shared.status.get() == Status::STOPPED {
             break;
         }
@@ -274,10 +327,23 @@ pub fn start(td: &mut ThreadData, report: Report, thread_count: usize) {
         td.print_uci_info(td.root_depth);
     }
 
+    #[cfg(feature = "trace")]
+    if let Err(error) = td.trace.finish(&td.board) {
+        eprintln!("Failed to write search trace: {error}");
+    }
+
     td.previous_best_score = td.root_moves[0].score;
 }
 
-fn search<NODE: NodeType>(
+#[inline(always)]
+fn search<NODE: NodeType>(td: &mut ThreadData, alpha: i32, beta: i32, depth: i32, cut_node: bool, ply: isize) -> i32 {
+    trace!(td, enter(ply, td.stack[ply - 1].mv, alpha, beta, depth, false));
+    let score = search_node::<NODE>(td, alpha, beta, depth, cut_node, ply);
+    trace!(td, exit(score));
+    score
+}
+
+fn search_node<NODE: NodeType>(
     td: &mut ThreadData, mut alpha: i32, mut beta: i32, depth: i32, cut_node: bool, ply: isize,
 ) -> i32 {
     debug_assert!(ply as usize <= MAX_PLY);
@@ -298,7 +364,7 @@ fn search<NODE: NodeType>(
 
     // Qsearch Dive
     if depth <= 0 {
-        return qsearch::<NODE>(td, alpha, beta, ply);
+        return qsearch::<NODE>(td, alpha, beta, ply, true);
     }
 
     let draw_score = draw(td);
@@ -346,6 +412,9 @@ fn search<NODE: NodeType>(
     let hash = td.board.hash();
     let entry = td.shared.tt.read(hash, td.board.fiftymove_clock(), ply);
 
+    record!(td, TtProbes);
+    record!(td, TtHits, entry.is_some());
+
     let mut tt_depth = 0;
     let mut tt_move = Move::NULL;
     let mut tt_score = Score::NONE;
@@ -381,6 +450,7 @@ fn search<NODE: NodeType>(
             }
 
             if td.board.fiftymove_clock() < 90 {
+                trace!(td, decision("tt"));
                 return tt_score;
             }
         }
@@ -410,6 +480,7 @@ fn search<NODE: NodeType>(
         {
             let depth = (depth + 6).min(MAX_PLY as i32 - 1);
             td.shared.tt.write(hash, depth, Score::NONE, score, bound, Move::NULL, ply, tt_pv, false);
+            trace!(td, decision("tb"));
             return score;
         }
 
@@ -462,6 +533,7 @@ fn search<NODE: NodeType>(
 
     td.stack[ply].eval = eval;
     td.stack[ply].tt_move = tt_move;
+    trace!(td, eval(eval));
     td.stack[ply].tt_pv = tt_pv;
     td.stack[ply].reduction = 0;
     td.stack[ply].move_count = 0;
@@ -521,7 +593,8 @@ fn search<NODE: NodeType>(
         && !tt_move.is_quiet()
         && tt_bound != Bound::Lower
     {
-        return qsearch::<NonPV>(td, alpha, beta, ply);
+        trace!(td, decision("razoring"));
+        return qsearch::<NonPV>(td, alpha, beta, ply, false);
     }
 
     // Reverse Futility Pruning (RFP)
@@ -539,6 +612,7 @@ fn search<NODE: NodeType>(
         && !is_loss(beta)
         && !is_win(estimated_score)
     {
+        trace!(td, decision("rfp"));
         return lerp(estimated_score, beta, 0.63);
     }
 
@@ -563,6 +637,7 @@ fn search<NODE: NodeType>(
             && td.board.piece_on(tt_move.to()).value() >= PieceType::Knight.value())
     {
         debug_assert_ne!(td.stack[ply - 1].mv, Move::NULL);
+        record!(td, NmpTries);
 
         let r =
             (4311 + 1024 * improving as i32 + 260 * depth + 493 * (estimated_score - beta).clamp(0, 1003) / 128) / 1024;
@@ -591,6 +666,8 @@ fn search<NODE: NodeType>(
 
         if score >= bound && !is_win(score) {
             if td.nmp_min_ply > 0 || depth < 16 {
+                record!(td, NmpCutoffs);
+                trace!(td, decision("nmp"));
                 return score;
             }
 
@@ -603,6 +680,8 @@ fn search<NODE: NodeType>(
             }
 
             if verified_score >= bound {
+                record!(td, NmpCutoffs);
+                trace!(td, decision("nmp"));
                 return score;
             }
         }
@@ -617,6 +696,7 @@ fn search<NODE: NodeType>(
         && !tt_move.is_quiet()
     {
         let mut move_picker = MovePicker::new(Move::NULL, Some(probcut_beta - eval));
+        record!(td, ProbCutTries);
 
         while let Some(mv) = move_picker.next::<NODE>(td, true, ply) {
             if move_picker.stage() == Stage::BadNoisy {
@@ -629,7 +709,7 @@ fn search<NODE: NodeType>(
 
             make_move(td, ply, mv);
 
-            let mut score = -qsearch::<NonPV>(td, -probcut_beta, -probcut_beta + 1, ply + 1);
+            let mut score = -qsearch::<NonPV>(td, -probcut_beta, -probcut_beta + 1, ply + 1, false);
 
             let base_depth = (depth - 4).max(0);
             let mut probcut_depth = (base_depth - (score - probcut_beta) / 305).clamp(0, base_depth);
@@ -655,6 +735,8 @@ fn search<NODE: NodeType>(
 
             if score >= probcut_beta {
                 td.shared.tt.write(hash, probcut_depth + 1, raw_eval, score, Bound::Lower, mv, ply, tt_pv, false);
+                record!(td, ProbCutCutoffs);
+                trace!(td, decision("probcut"));
 
                 if is_decisive(score) {
                     return score;
@@ -676,6 +758,7 @@ fn search<NODE: NodeType>(
         let singular_beta = tt_score - singular_margin;
         let singular_depth = (depth - 1) / 2;
 
+        record!(td, SeTries);
         td.excluded[ply] = tt_move;
         td.stack[ply].mv = Move::NULL;
         singular_score = search::<NonPV>(td, singular_beta - 1, singular_beta, singular_depth, cut_node, ply);
@@ -695,12 +778,15 @@ fn search<NODE: NodeType>(
                 - 13 * correction_value.abs() / 128
                 + 33;
 
+            record!(td, SeExtensions);
             extension = 1;
             extension += (singular_score < singular_beta - double_margin) as i32;
             extension += (singular_score < singular_beta - triple_margin) as i32;
         }
         // Multi-Cut
         else if singular_score >= beta && !is_decisive(singular_score) {
+            record!(td, SeMultiCuts);
+            trace!(td, decision("multicut"));
             return lerp(singular_score, beta, 0.34);
         } else if singular_score > tt_score && td.stack[ply].mv != Move::NULL {
             tt_move = Move::NULL;
@@ -741,6 +827,10 @@ fn search<NODE: NodeType>(
         move_count += 1;
         td.stack[ply].move_count = move_count;
 
+        if NODE::ROOT {
+            td.print_currmove(mv, move_count as usize + td.pv_index);
+        }
+
         let is_quiet = mv.is_quiet();
         let is_direct_check = td.board.is_direct_check(mv);
 
@@ -879,6 +969,7 @@ fn search<NODE: NodeType>(
             score = -search::<NonPV>(td, -alpha - 1, -alpha, reduced_depth, true, ply + 1);
             td.stack[ply].reduction = 0;
             current_search_count += 1;
+            record!(td, LmrSearches);
 
             if score > alpha {
                 if !NODE::ROOT {
@@ -889,6 +980,7 @@ fn search<NODE: NodeType>(
                 if new_depth > reduced_depth {
                     score = -search::<NonPV>(td, -alpha - 1, -alpha, new_depth, !cut_node, ply + 1);
                     current_search_count += 1;
+                    record!(td, LmrResearches);
                 }
             }
         }
@@ -1151,12 +1243,25 @@ fn search<NODE: NodeType>(
     best_score
 }
 
-fn qsearch<NODE: NodeType>(td: &mut ThreadData, mut alpha: i32, beta: i32, ply: isize) -> i32 {
+#[inline(always)]
+fn qsearch<NODE: NodeType>(td: &mut ThreadData, alpha: i32, beta: i32, ply: isize, checks: bool) -> i32 {
+    trace!(td, enter(ply, td.stack[ply - 1].mv, alpha, beta, 0, true));
+    let score = qsearch_node::<NODE>(td, alpha, beta, ply, checks);
+    trace!(td, exit(score));
+    score
+}
+
+/// Searches noisy moves until the position is quiet, or every evasion when in check. When `checks`
+/// is set, which is only the case at the first ply dropped into from the main search, quiet checks
+/// are tried after the good noisy moves.
+fn qsearch_node<NODE: NodeType>(td: &mut ThreadData, mut alpha: i32, beta: i32, ply: isize, checks: bool) -> i32 {
     debug_assert!(!NODE::ROOT);
     debug_assert!(ply as usize <= MAX_PLY);
     debug_assert!(-Score::INFINITE <= alpha && alpha < beta && beta <= Score::INFINITE);
     debug_assert!(NODE::PV || alpha == beta - 1);
 
+    record!(td, QsearchNodes);
+
     let draw_score = draw(td);
     if alpha < draw_score && td.board.upcoming_repetition(ply as usize) {
         alpha = draw_score;
@@ -1188,6 +1293,9 @@ fn qsearch<NODE: NodeType>(td: &mut ThreadData, mut alpha: i32, beta: i32, ply:
     let hash = td.board.hash();
     let entry = td.shared.tt.read(hash, td.board.fiftymove_clock(), ply);
 
+    record!(td, TtProbes);
+    record!(td, TtHits, entry.is_some());
+
     let mut tt_score = Score::NONE;
     let mut tt_bound = Bound::None;
     let mut tt_pv = NODE::PV;
@@ -1206,6 +1314,7 @@ fn qsearch<NODE: NodeType>(td: &mut ThreadData, mut alpha: i32, beta: i32, ply:
                 _ => true,
             }
         {
+            trace!(td, decision("tt"));
             return tt_score;
         }
     }
@@ -1241,6 +1350,8 @@ fn qsearch<NODE: NodeType>(td: &mut ThreadData, mut alpha: i32, beta: i32, ply:
         }
     }
 
+    trace!(td, eval(eval));
+
     // Stand Pat
     if best_score >= beta {
         if !is_decisive(best_score) && !is_decisive(beta) {
@@ -1251,6 +1362,7 @@ fn qsearch<NODE: NodeType>(td: &mut ThreadData, mut alpha: i32, beta: i32, ply:
             td.shared.tt.write(hash, TtDepth::SOME, raw_eval, best_score, Bound::Lower, Move::NULL, ply, tt_pv, false);
         }
 
+        trace!(td, decision("stand pat"));
         return best_score;
     }
 
@@ -1261,7 +1373,7 @@ fn qsearch<NODE: NodeType>(td: &mut ThreadData, mut alpha: i32, beta: i32, ply:
     let mut best_move = Move::NULL;
 
     let mut move_count = 0;
-    let mut move_picker = MovePicker::new(Move::NULL, None);
+    let mut move_picker = MovePicker::new_qsearch(in_check, checks);
 
     let skip_quiets = |best_score| !in_check || !is_loss(best_score);
 
@@ -1270,7 +1382,7 @@ fn qsearch<NODE: NodeType>(td: &mut ThreadData, mut alpha: i32, beta: i32, ply:
 
         if !is_loss(best_score) {
             // Late Move Pruning (LMP)
-            if move_count >= 3 && !td.board.is_direct_check(mv) {
+            if move_count >= 3 && !td.board.gives_check(mv) {
                 break;
             }
 
@@ -1281,7 +1393,7 @@ fn qsearch<NODE: NodeType>(td: &mut ThreadData, mut alpha: i32, beta: i32, ply:
         }
 
         make_move(td, ply, mv);
-        let score = -qsearch::<NODE>(td, -beta, -alpha, ply + 1);
+        let score = -qsearch::<NODE>(td, -beta, -alpha, ply + 1, false);
         undo_move(td, mv);
 
         if td.shared.status.get() == Status::STOPPED {
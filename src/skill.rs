@@ -0,0 +1,113 @@
+//! Strength limiting through the `Skill Level` option.
+//!
+//! A weakened engine searches with a capped node budget and several principal variations,
+//! then picks its move at random among them, favouring the better ones. The lower the level,
+//! the smaller the budget and the more likely a weaker move is chosen.
+//!
+//! The levels have not been calibrated against rated engines, so they order the engine's
+//! strength but do not correspond to Elo ratings; `UCI_Elo` is deliberately not offered.
+
+use crate::{random::Random, thread::RootMove, types::Score};
+
+pub const MAX_LEVEL: f64 = 20.0;
+
+/// The minimum number of principal variations searched when strength limiting is enabled.
+pub const MULTI_PV: usize = 4;
+
+pub struct Skill {
+    level: f64,
+}
+
+impl Skill {
+    pub const fn new(level: f64) -> Self {
+        Self { level }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.level < MAX_LEVEL
+    }
+
+    /// The maximum number of nodes the search may visit for a single move, doubling about
+    /// every one and a half levels from 64 nodes at level 0.
+    pub fn node_limit(&self) -> u64 {
+        (64.0 * 2f64.powf(0.6 * self.level)) as u64
+    }
+
+    /// Picks the index of the move to play among the first `multi_pv` root moves.
+    ///
+    /// Every candidate gets a random bonus that grows with its distance from the best score
+    /// and with the weakness of the level; the move with the highest adjusted score wins.
+    pub fn pick_move(&self, root_moves: &[RootMove], multi_pv: usize, rng: &mut Random) -> usize {
+        let score = |rm: &RootMove| if rm.score == -Score::INFINITE { rm.previous_score } else { rm.score };
+
+        let candidates = root_moves[..multi_pv.min(root_moves.len())]
+            .iter()
+            .take_while(|rm| score(rm) != -Score::INFINITE)
+            .collect::<Vec<_>>();
+
+        if candidates.len() < 2 {
+            return 0;
+        }
+
+        let top_score = score(candidates[0]);
+        let delta = (top_score - score(candidates[candidates.len() - 1])).min(200);
+        let weakness = (120.0 - 2.0 * self.level) as i32;
+
+        let mut best_index = 0;
+        let mut best_score = -Score::INFINITE;
+
+        for (index, &rm) in candidates.iter().enumerate() {
+            let push = (weakness * (top_score - score(rm)) + delta * rng.below(weakness as u64) as i32) / 128;
+
+            if score(rm) + push >= best_score {
+                best_score = score(rm) + push;
+                best_index = index;
+            }
+        }
+
+        best_index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn root_moves(scores: &[i32]) -> Vec<RootMove> {
+        scores.iter().map(|&score| RootMove { score, ..Default::default() }).collect()
+    }
+
+    #[test]
+    fn test_levels() {
+        assert!((0..MAX_LEVEL as usize).all(|level| Skill::new(level as f64).enabled()));
+        assert!(!Skill::new(MAX_LEVEL).enabled());
+
+        let limits = (0..MAX_LEVEL as usize).map(|level| Skill::new(level as f64).node_limit()).collect::<Vec<_>>();
+        assert!(limits.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_pick_move() {
+        let mut rng = Random::new(0);
+
+        assert_eq!(Skill::new(0.0).pick_move(&root_moves(&[10]), MULTI_PV, &mut rng), 0);
+        assert_eq!(Skill::new(0.0).pick_move(&root_moves(&[10, -Score::INFINITE]), MULTI_PV, &mut rng), 0);
+
+        let moves = root_moves(&[50, 40, 0, -30, 100]);
+        let best_picks = |level: f64| {
+            let skill = Skill::new(level);
+            let mut rng = Random::new(1);
+            let mut best = 0;
+
+            for _ in 0..1000 {
+                let index = skill.pick_move(&moves, MULTI_PV, &mut rng);
+                assert!(index < MULTI_PV);
+                best += (index == 0) as usize;
+            }
+            best
+        };
+
+        assert!(best_picks(0.0) < best_picks(10.0));
+        assert!(best_picks(10.0) < best_picks(MAX_LEVEL - 1.0));
+    }
+}
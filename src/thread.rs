@@ -1,23 +1,27 @@
 use std::{
     ops::{Index, IndexMut},
     sync::{
-        Arc,
+        Arc, Condvar, Mutex,
         atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering},
     },
 };
 
 use crate::{
-    board::Board,
+    board::{Board, NullBoardObserver},
     history::{ContinuationCorrectionHistory, ContinuationHistory, CorrectionHistory, NoisyHistory, QuietHistory},
     nnue::{Network, ParametersHandle},
     numa::{NumaConfig, NumaReplicable, NumaReplicated, NumaReplicatedAccessToken, NumaReplicationContext},
+    search::Report,
     stack::Stack,
     threadpool::ThreadPool,
     time::{Limits, TimeManager},
     transposition::TranspositionTable,
-    types::{MAX_MOVES, MAX_PLY, Move, Score, normalize_to_cp},
+    types::{MAX_MOVES, MAX_PLY, Move, Score, normalize_to_cp, win_rate_model},
 };
 
+/// Time in milliseconds after which the move currently searched at the root is reported.
+pub const DEFAULT_CURRMOVE_THRESHOLD: u64 = 3000;
+
 #[repr(align(64))]
 struct AlignedAtomicU64 {
     inner: AtomicU64,
@@ -60,6 +64,63 @@ impl Default for Counter {
     }
 }
 
+/// Search events counted for the diagnostics enabled with the UCI `debug on` command.
+#[cfg(feature = "stats")]
+#[derive(Copy, Clone)]
+pub enum Stat {
+    TtProbes,
+    TtHits,
+    QsearchNodes,
+    NmpTries,
+    NmpCutoffs,
+    ProbCutTries,
+    ProbCutCutoffs,
+    SeTries,
+    SeExtensions,
+    SeMultiCuts,
+    LmrSearches,
+    LmrResearches,
+}
+
+#[cfg(feature = "stats")]
+impl Stat {
+    pub const NUM: usize = 12;
+}
+
+/// Set while a `go ponder` search runs on the predicted move, until `ponderhit` or `stop`.
+///
+/// The search reads the flag without locking, while the UCI thread can block in
+/// [`Pondering::wait`] until it is cleared instead of polling it.
+#[derive(Default)]
+pub struct Pondering {
+    active: AtomicBool,
+    lock: Mutex<()>,
+    ended: Condvar,
+}
+
+impl Pondering {
+    pub fn get(&self) -> bool {
+        self.active.load(Ordering::Acquire)
+    }
+
+    pub fn set(&self, pondering: bool) {
+        let _guard = self.lock.lock().unwrap();
+        self.active.store(pondering, Ordering::Release);
+
+        if !pondering {
+            self.ended.notify_all();
+        }
+    }
+
+    /// Blocks until pondering ends.
+    pub fn wait(&self) {
+        let mut guard = self.lock.lock().unwrap();
+        while self.get() {
+            guard = self.ended.wait(guard).unwrap();
+        }
+    }
+}
+
 pub struct Status {
     inner: AtomicUsize,
 }
@@ -108,6 +169,13 @@ pub struct SharedContext {
     pub tb_hits: Counter,
     pub stop_probing_tb: AtomicBool,
     pub root_in_tb: AtomicBool,
+    pub pondering: Pondering,
+    pub show_wdl: AtomicBool,
+    pub json_output: AtomicBool,
+    pub currmove_threshold: AtomicU64,
+    pub debug: AtomicBool,
+    #[cfg(feature = "stats")]
+    pub stats: [Counter; Stat::NUM],
     pub soft_stop_votes: AtomicUsize,
     pub best_stats: [AtomicU32; MAX_MOVES],
     pub history: Arc<NumaReplicated<SharedCorrectionHistory>>,
@@ -126,6 +194,13 @@ impl Default for SharedContext {
             tb_hits: Counter::default(),
             stop_probing_tb: AtomicBool::new(false),
             root_in_tb: AtomicBool::new(false),
+            pondering: Pondering::default(),
+            show_wdl: AtomicBool::new(false),
+            json_output: AtomicBool::new(false),
+            currmove_threshold: AtomicU64::new(DEFAULT_CURRMOVE_THRESHOLD),
+            debug: AtomicBool::new(false),
+            #[cfg(feature = "stats")]
+            stats: std::array::from_fn(|_| Counter::default()),
             soft_stop_votes: AtomicUsize::new(0),
             best_stats: [const { AtomicU32::new(0) }; MAX_MOVES],
             history: NumaReplicated::new(numa_context.clone()),
@@ -181,11 +256,14 @@ pub struct ThreadData {
     pub nmp_min_ply: i32,
     pub previous_best_score: i32,
     pub multi_pv: usize,
+    pub report: Report,
     pub pv_index: usize,
     pub pv_start: usize,
     pub pv_end: usize,
     pub cutoff_count: PlyArray<i32, { MAX_PLY + 16 }>,
     pub excluded: PlyArray<Move, { MAX_PLY + 16 }>,
+    #[cfg(feature = "trace")]
+    pub trace: crate::trace::Tracer,
 }
 
 impl ThreadData {
@@ -198,7 +276,7 @@ impl ThreadData {
             shared,
             corrhist,
             board: Board::starting_position(),
-            time_manager: TimeManager::new(Limits::Infinite, 0, 0),
+            time_manager: TimeManager::new(Limits::default(), 0, 0),
             stack: Stack::new(),
             nnue: Network::new(parameters),
             root_moves: Vec::new(),
@@ -216,11 +294,14 @@ impl ThreadData {
             nmp_min_ply: 0,
             previous_best_score: 0,
             multi_pv: 1,
+            report: Report::None,
             pv_index: 0,
             pv_start: 0,
             pv_end: 0,
             cutoff_count: PlyArray::default(),
             excluded: PlyArray::default(),
+            #[cfg(feature = "trace")]
+            trace: crate::trace::Tracer::default(),
         }
     }
 
@@ -228,6 +309,13 @@ impl ThreadData {
         self.shared.nodes.get(self.id)
     }
 
+    #[cfg(feature = "stats")]
+    pub fn record(&self, stat: Stat) {
+        if self.shared.debug.load(Ordering::Relaxed) {
+            self.shared.stats[stat as usize].increment(self.id);
+        }
+    }
+
     pub fn corrhist(&self) -> &SharedCorrectionHistory {
         &self.corrhist
     }
@@ -237,14 +325,22 @@ impl ThreadData {
     }
 
     pub fn print_uci_info(&self, depth: i32) {
+        for line in self.uci_info(depth) {
+            println!("{line}");
+        }
+    }
+
+    /// Formats the `info` lines of every principal variation, as UCI text or JSON objects.
+    fn uci_info(&self, depth: i32) -> Vec<String> {
         if self.root_moves.is_empty() {
-            self.print_uci_no_move();
-            return;
+            return vec![self.uci_no_move()];
         }
 
-        let elapsed = self.time_manager.elapsed();
-        let nps = self.shared.nodes.aggregate() as f64 / elapsed.as_secs_f64();
-        let ms = elapsed.as_millis();
+        // Integer milliseconds keep the rate finite right after the search started
+        let ms = self.time_manager.elapsed().as_millis() as u64;
+        let nps = self.shared.nodes.aggregate() * 1000 / ms.max(1);
+
+        let mut lines = Vec::new();
 
         for pv_index in 0..self.multi_pv {
             let root_move = &self.root_moves[pv_index];
@@ -277,28 +373,57 @@ impl ThreadData {
                 }
             }
 
-            let mut formatted_score = match score.abs() {
-                s if s < Score::TB_WIN_IN_MAX => {
-                    format!("cp {}", normalize_to_cp(score, &self.board))
-                }
-                s if s <= Score::TB_WIN => {
-                    let cp = 20_000 - Score::TB_WIN + score.abs();
-                    format!("cp {}", if score.is_positive() { cp } else { -cp })
-                }
-                _ => {
-                    let mate = (Score::MATE - score.abs() + score.is_positive() as i32) / 2;
-                    format!("mate {}", if score.is_positive() { mate } else { -mate })
-                }
+            let (kind, value) = uci_score(score, &self.board);
+
+            let bound = match (upperbound, lowerbound) {
+                (true, _) => Some("upperbound"),
+                (false, true) => Some("lowerbound"),
+                (false, false) => None,
             };
 
-            if upperbound {
-                formatted_score.push_str(" upperbound");
-            } else if lowerbound {
-                formatted_score.push_str(" lowerbound");
+            let wdl = self.shared.show_wdl.load(Ordering::Relaxed).then(|| win_rate_model(score, &self.board));
+
+            let mut pv = vec![root_move.mv];
+            pv.extend_from_slice(root_move.pv.line());
+
+            if self.shared.json_output.load(Ordering::Relaxed) {
+                let mut board = self.board.clone();
+                let san = pv
+                    .iter()
+                    .map(|&mv| {
+                        let san = format!("\"{}\"", mv.to_san(&board));
+                        board.make_move(mv, &mut NullBoardObserver);
+                        san
+                    })
+                    .collect::<Vec<_>>();
+                let uci = pv.iter().map(|mv| format!("\"{}\"", mv.to_uci(&self.board))).collect::<Vec<_>>();
+
+                lines.push(format!(
+                    "{{\"depth\":{depth},\"seldepth\":{},\"multipv\":{},\"score\":{},\"nodes\":{},\"time\":{ms},\"nps\":{nps},\"hashfull\":{},\"tbhits\":{},\"pv\":[{}],\"pv_san\":[{}]}}",
+                    root_move.sel_depth,
+                    pv_index + 1,
+                    json_score(kind, value, bound, wdl),
+                    self.shared.nodes.aggregate(),
+                    self.shared.tt.hashfull(),
+                    self.shared.tb_hits.aggregate(),
+                    uci.join(","),
+                    san.join(","),
+                ));
+                continue;
+            }
+
+            let mut formatted_score = format!("{kind} {value}");
+
+            if let Some(bound) = bound {
+                formatted_score.push_str(&format!(" {bound}"));
+            }
+
+            if let Some((win, draw, loss)) = wdl {
+                formatted_score.push_str(&format!(" wdl {win} {draw} {loss}"));
             }
 
-            print!(
-                "info depth {depth} seldepth {} multipv {} score {formatted_score} nodes {} time {ms} nps {nps:.0} hashfull {} tbhits {} pv",
+            let mut line = format!(
+                "info depth {depth} seldepth {} multipv {} score {formatted_score} nodes {} time {ms} nps {nps} hashfull {} tbhits {} pv",
                 root_move.sel_depth,
                 pv_index + 1,
                 self.shared.nodes.aggregate(),
@@ -306,24 +431,129 @@ impl ThreadData {
                 self.shared.tb_hits.aggregate(),
             );
 
-            print!(" {}", root_move.mv.to_uci(&self.board));
-            for mv in root_move.pv.line() {
-                print!(" {}", mv.to_uci(&self.board));
+            for mv in pv {
+                line.push_str(&format!(" {}", mv.to_uci(&self.board)));
             }
 
-            println!();
+            lines.push(line);
         }
+
+        lines
     }
 
-    fn print_uci_no_move(&self) {
+    /// Reports the diagnostics collected by all threads so far, where `ebf` is
+    /// the effective branching factor of the iteration that just finished.
+    #[cfg(feature = "stats")]
+    pub fn print_debug_info(&self, depth: i32, ebf: f64) {
+        let stat = |stat: Stat| self.shared.stats[stat as usize].aggregate();
+        let ratio = |a: u64, b: u64| 100.0 * a as f64 / b.max(1) as f64;
+
+        let tt_hits = ratio(stat(Stat::TtHits), stat(Stat::TtProbes));
+        let qsearch = ratio(stat(Stat::QsearchNodes), self.shared.nodes.aggregate());
+        let lmr = ratio(stat(Stat::LmrResearches), stat(Stat::LmrSearches));
+
+        if self.shared.json_output.load(Ordering::Relaxed) {
+            println!(
+                "{{\"debug\":{{\"depth\":{depth},\"tthits\":{tt_hits:.1},\"nmp\":[{},{}],\"probcut\":[{},{}],\"se\":[{},{},{}],\"lmr\":[{},{}],\"qsearch\":{qsearch:.1},\"ebf\":{ebf:.2}}}}}",
+                stat(Stat::NmpCutoffs),
+                stat(Stat::NmpTries),
+                stat(Stat::ProbCutCutoffs),
+                stat(Stat::ProbCutTries),
+                stat(Stat::SeExtensions),
+                stat(Stat::SeMultiCuts),
+                stat(Stat::SeTries),
+                stat(Stat::LmrResearches),
+                stat(Stat::LmrSearches),
+            );
+            return;
+        }
+
+        println!(
+            "info string debug depth {depth} tthits {tt_hits:.1}% nmp {}/{} ({:.1}%) probcut {}/{} ({:.1}%) se {}/{} ({:.1}%) multicut {} lmr-research {}/{} ({lmr:.1}%) qsearch {qsearch:.1}% ebf {ebf:.2}",
+            stat(Stat::NmpCutoffs),
+            stat(Stat::NmpTries),
+            ratio(stat(Stat::NmpCutoffs), stat(Stat::NmpTries)),
+            stat(Stat::ProbCutCutoffs),
+            stat(Stat::ProbCutTries),
+            ratio(stat(Stat::ProbCutCutoffs), stat(Stat::ProbCutTries)),
+            stat(Stat::SeExtensions),
+            stat(Stat::SeTries),
+            ratio(stat(Stat::SeExtensions), stat(Stat::SeTries)),
+            stat(Stat::SeMultiCuts),
+            stat(Stat::LmrResearches),
+            stat(Stat::LmrSearches),
+        );
+    }
+
+    /// Reports the root move being searched, once the search has been running for long enough.
+    pub fn print_currmove(&self, mv: Move, number: usize) {
+        if self.id != 0
+            || self.report != Report::Full
+            || (self.time_manager.elapsed().as_millis() as u64) < self.shared.currmove_threshold.load(Ordering::Relaxed)
+        {
+            return;
+        }
+
+        if self.shared.json_output.load(Ordering::Relaxed) {
+            println!("{{\"currmove\":\"{}\",\"currmovenumber\":{number}}}", mv.to_uci(&self.board));
+        } else {
+            println!("info currmove {} currmovenumber {number}", mv.to_uci(&self.board));
+        }
+    }
+
+    fn uci_no_move(&self) -> String {
+        if self.shared.json_output.load(Ordering::Relaxed) {
+            let (kind, wdl) = if self.board.in_check() { ("mate", (0, 0, 1000)) } else { ("cp", (0, 1000, 0)) };
+            let wdl = self.shared.show_wdl.load(Ordering::Relaxed).then_some(wdl);
+
+            return format!("{{\"depth\":0,\"score\":{},\"pv\":[],\"pv_san\":[]}}", json_score(kind, 0, None, wdl));
+        }
+
+        let wdl = match (self.shared.show_wdl.load(Ordering::Relaxed), self.board.in_check()) {
+            (false, _) => "",
+            (true, true) => " wdl 0 0 1000",
+            (true, false) => " wdl 0 1000 0",
+        };
+
         if self.board.in_check() {
-            println!("info depth 0 score mate 0");
+            format!("info depth 0 score mate 0{wdl}")
         } else {
-            println!("info depth 0 score cp 0");
+            format!("info depth 0 score cp 0{wdl}")
+        }
+    }
+}
+
+/// Converts an internal score to the kind (`cp` or `mate`) and value reported over UCI.
+pub fn uci_score(score: i32, board: &Board) -> (&'static str, i32) {
+    match score.abs() {
+        s if s < Score::TB_WIN_IN_MAX => ("cp", normalize_to_cp(score, board)),
+        s if s <= Score::TB_WIN => {
+            let cp = 20_000 - Score::TB_WIN + score.abs();
+            ("cp", if score.is_positive() { cp } else { -cp })
+        }
+        _ => {
+            let mate = (Score::MATE - score.abs() + score.is_positive() as i32) / 2;
+            ("mate", if score.is_positive() { mate } else { -mate })
         }
     }
 }
 
+/// Formats a score as a JSON object, with the bound and win/draw/loss probabilities if present.
+fn json_score(kind: &str, value: i32, bound: Option<&str>, wdl: Option<(i32, i32, i32)>) -> String {
+    let mut output = format!("{{\"kind\":\"{kind}\",\"value\":{value}");
+
+    if let Some(bound) = bound {
+        output.push_str(&format!(",\"bound\":\"{bound}\""));
+    }
+
+    if let Some((win, draw, loss)) = wdl {
+        output.push_str(&format!(",\"wdl\":[{win},{draw},{loss}]"));
+    }
+
+    output.push('}');
+    output
+}
+
 #[derive(Clone)]
 pub struct RootMove {
     pub mv: Move,
@@ -396,3 +626,57 @@ impl Default for PrincipalVariationTable {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::Limits;
+
+    /// Returns the raw text of the value of `key` in a line of JSON output.
+    fn field<'a>(json: &'a str, key: &str) -> &'a str {
+        let start = json.find(&format!("\"{key}\":")).unwrap_or_else(|| panic!("missing {key}: {json}"));
+        let value = &json[start + key.len() + 3..];
+
+        let end = match value.as_bytes()[0] {
+            b'[' => value.find(']').unwrap() + 1,
+            b'{' => value.find('}').unwrap() + 1,
+            _ => value.find([',', '}']).unwrap(),
+        };
+        &value[..end]
+    }
+
+    #[test]
+    fn test_json_info() {
+        let shared = Arc::new(SharedContext::default());
+        shared.json_output.store(true, Ordering::Relaxed);
+        shared.show_wdl.store(true, Ordering::Relaxed);
+
+        let mut pool = ThreadPool::new(shared.clone());
+        let board = Board::starting_position();
+        let limits = Limits { depth: Some(3), ..Default::default() };
+        pool.execute_searches(TimeManager::new(limits, 0, 0), Report::None, 2, None, &board, &shared);
+
+        let lines = pool.main_thread().uci_info(3);
+        assert_eq!(lines.len(), 2);
+
+        for (index, line) in lines.iter().enumerate() {
+            assert!(line.starts_with("{\"depth\":3,") && line.ends_with("]}"), "{line}");
+            assert_eq!(field(line, "multipv"), (index + 1).to_string());
+            assert!(field(line, "nps").parse::<u64>().is_ok(), "{line}");
+            assert!(field(line, "pv").starts_with("[\"") && field(line, "pv_san").starts_with("[\""), "{line}");
+
+            let score = field(line, "score");
+            assert!(score.starts_with("{\"kind\":\"cp\","), "{line}");
+
+            let wdl = field(score, "wdl").trim_matches(['[', ']']).split(',').map(|v| v.parse::<i32>().unwrap());
+            assert_eq!(wdl.sum::<i32>(), 1000);
+        }
+
+        let mated = Board::from_fen("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3").unwrap();
+        pool.execute_searches(TimeManager::new(Limits::default(), 0, 0), Report::None, 1, None, &mated, &shared);
+
+        let lines = pool.main_thread().uci_info(0);
+        assert!(field(&lines[0], "score").starts_with("{\"kind\":\"mate\","), "{}", lines[0]);
+        assert_eq!(field(&lines[0], "pv"), "[]");
+    }
+}
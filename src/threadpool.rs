@@ -16,6 +16,7 @@ use crate::{
     search::{self, Report},
     thread::{RootMove, SharedContext, Status, ThreadData},
     time::TimeManager,
+    types::Move,
 };
 
 pub struct ThreadPool {
@@ -76,14 +77,33 @@ impl ThreadPool {
         self.vector = make_thread_data(shared, &self.workers);
     }
 
+    /// Runs `f` on every worker thread, passing the index of the thread, and waits for all of them to finish.
+    pub fn broadcast<F: Fn(usize) + Sync>(&self, f: F) {
+        std::thread::scope(|scope| {
+            let f = &f;
+            let handlers = self
+                .workers
+                .iter()
+                .enumerate()
+                .map(|(index, worker)| scope.spawn_into(move || f(index), worker))
+                .collect::<Vec<_>>();
+
+            for handler in handlers {
+                handler.join();
+            }
+        });
+    }
+
     pub fn execute_searches(
-        &mut self, time_manager: TimeManager, report: Report, multi_pv: usize, board: &Board,
-        shared: &Arc<SharedContext>,
+        &mut self, time_manager: TimeManager, report: Report, multi_pv: usize, search_moves: Option<&[Move]>,
+        board: &Board, shared: &Arc<SharedContext>,
     ) {
         shared.tt.increment_age();
 
         shared.nodes.reset();
         shared.tb_hits.reset();
+        #[cfg(feature = "stats")]
+        shared.stats.iter().for_each(crate::thread::Counter::reset);
         shared.soft_stop_votes.store(0, Ordering::Release);
         shared.status.set(Status::RUNNING);
         shared.best_stats.iter().for_each(|x| {
@@ -102,8 +122,13 @@ impl ThreadPool {
             t1.shared.stop_probing_tb.store(false, Ordering::Relaxed);
 
             t1.board = (*board).clone();
-            t1.root_moves =
-                t1.board.generate_all_moves().iter().map(|v| RootMove { mv: v.mv, ..Default::default() }).collect();
+            t1.root_moves = t1
+                .board
+                .generate_all_moves()
+                .iter()
+                .filter(|v| search_moves.is_none_or(|moves| moves.contains(&v.mv)))
+                .map(|v| RootMove { mv: v.mv, ..Default::default() })
+                .collect();
 
             #[cfg(feature = "syzygy")]
             if t1.board.castling().raw() == 0 && t1.board.occupancies().popcount() <= tb::size() && !t1.board.is_draw(0)
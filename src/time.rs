@@ -2,63 +2,83 @@ use std::time::{Duration, Instant};
 
 use crate::thread::ThreadData;
 
-#[derive(Clone, Debug)]
-pub enum Limits {
-    Infinite,
-    Depth(i32),
-    Time(u64),
-    Nodes(u64),
+/// Search limits as received from the `go` command.
+///
+/// Any number of limits can be combined, in which case the search stops
+/// as soon as the first of them is reached. No limits at all means an infinite search.
+#[derive(Clone, Debug, Default)]
+pub struct Limits {
+    pub depth: Option<i32>,
+    pub nodes: Option<u64>,
+    pub movetime: Option<u64>,
+    pub mate: Option<i32>,
+    pub clock: Option<Clock>,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum Clock {
     Fischer(u64, u64),
     Cyclic(u64, u64, u64),
 }
 
+impl Limits {
+    pub fn depth(depth: i32) -> Self {
+        Self { depth: Some(depth), ..Default::default() }
+    }
+
+    pub fn time(ms: u64) -> Self {
+        Self { movetime: Some(ms), ..Default::default() }
+    }
+
+    pub const fn is_timed(&self) -> bool {
+        self.movetime.is_some() || self.clock.is_some()
+    }
+}
+
 const TIME_OVERHEAD_MS: u64 = 15;
 
 #[derive(Clone)]
 pub struct TimeManager {
     limits: Limits,
     start_time: Instant,
-    soft_bound: Duration,
+    soft_bound: Option<Duration>,
     hard_bound: Duration,
 }
 
 impl TimeManager {
     pub fn new(limits: Limits, fullmove_number: usize, move_overhead: u64) -> Self {
-        let soft;
-        let hard;
+        let mut soft = None;
+        let mut hard = u64::MAX;
 
-        match limits {
-            Limits::Time(ms) => {
-                soft = ms;
-                hard = ms;
-            }
-            Limits::Fischer(main, inc) => {
+        match limits.clock {
+            Some(Clock::Fischer(main, inc)) => {
                 let soft_scale = 0.0599 - 0.0485 * (-0.0459 * fullmove_number as f64).exp();
                 let hard_scale = 0.7524;
 
                 let soft_bound = (soft_scale * main.saturating_sub(move_overhead) as f64 + 0.75 * inc as f64) as u64;
                 let hard_bound = (hard_scale * main.saturating_sub(move_overhead) as f64 + 0.75 * inc as f64) as u64;
 
-                soft = soft_bound.min(main.saturating_sub(move_overhead));
+                soft = Some(soft_bound.min(main.saturating_sub(move_overhead)));
                 hard = hard_bound.min(main.saturating_sub(move_overhead));
             }
-            Limits::Cyclic(main, inc, moves) => {
+            Some(Clock::Cyclic(main, inc, moves)) => {
                 let main = main.saturating_sub(move_overhead);
                 let base = (main as f64 / moves as f64) + 0.75 * inc as f64;
 
-                soft = ((1.0 * base) as u64).min(main + inc);
+                soft = Some(((1.0 * base) as u64).min(main + inc));
                 hard = ((5.0 * base) as u64).min(main + inc);
             }
-            _ => {
-                soft = u64::MAX;
-                hard = u64::MAX;
-            }
+            None => (),
+        }
+
+        if let Some(ms) = limits.movetime {
+            hard = hard.min(ms);
         }
 
         Self {
             limits,
             start_time: Instant::now(),
-            soft_bound: Duration::from_millis(soft.saturating_sub(TIME_OVERHEAD_MS)),
+            soft_bound: soft.map(|soft| Duration::from_millis(soft.saturating_sub(TIME_OVERHEAD_MS))),
             hard_bound: Duration::from_millis(hard.saturating_sub(TIME_OVERHEAD_MS)),
         }
     }
@@ -68,11 +88,25 @@ impl TimeManager {
     }
 
     pub fn soft_limit(&self, td: &ThreadData, multiplier: impl Fn() -> f32) -> bool {
-        match self.limits {
-            Limits::Infinite | Limits::Depth(_) => false,
-            Limits::Nodes(maximum) => td.shared.nodes.aggregate() >= maximum,
-            Limits::Time(maximum) => self.start_time.elapsed() >= Duration::from_millis(maximum),
-            _ => self.start_time.elapsed() >= Duration::from_secs_f32(self.soft_bound.as_secs_f32() * multiplier()),
+        // While pondering the search behaves as if it were infinite. Once `ponderhit`
+        // is received, the regular limits apply, measured from the start of `go`.
+        if td.shared.pondering.get() {
+            return false;
+        }
+
+        if self.limits.nodes.is_some_and(|maximum| td.shared.nodes.aggregate() >= maximum) {
+            return true;
+        }
+
+        if self.limits.movetime.is_some_and(|maximum| self.start_time.elapsed() >= Duration::from_millis(maximum)) {
+            return true;
+        }
+
+        match self.soft_bound {
+            Some(soft_bound) => {
+                self.start_time.elapsed() >= Duration::from_secs_f32(soft_bound.as_secs_f32() * multiplier())
+            }
+            None => false,
         }
     }
 
@@ -81,14 +115,14 @@ impl TimeManager {
             return false;
         }
 
-        match self.limits {
-            Limits::Infinite | Limits::Depth(_) => false,
-            Limits::Nodes(maximum) => td.shared.nodes.aggregate() > maximum,
-            _ => td.nodes() & 2047 == 2047 && self.start_time.elapsed() >= self.hard_bound,
-        }
+        let nodes_exceeded = self.limits.nodes.is_some_and(|maximum| td.shared.nodes.aggregate() > maximum);
+        let time_exceeded =
+            self.limits.is_timed() && td.nodes() & 2047 == 2047 && self.start_time.elapsed() >= self.hard_bound;
+
+        (nodes_exceeded || time_exceeded) && !td.shared.pondering.get()
     }
 
-    pub fn limits(&self) -> Limits {
-        self.limits.clone()
+    pub const fn limits(&self) -> &Limits {
+        &self.limits
     }
 }
@@ -100,9 +100,9 @@ pub fn bench<const PRETTY: bool>(args: &[&str]) {
         let now = Instant::now();
 
         let board = Board::from_fen(position).unwrap();
-        let time_manager = TimeManager::new(Limits::Depth(depth), 0, 0);
+        let time_manager = TimeManager::new(Limits::depth(depth), 0, 0);
 
-        pool.execute_searches(time_manager, Report::None, 1, &board, &shared);
+        pool.execute_searches(time_manager, Report::None, 1, None, &board, &shared);
 
         nodes += shared.nodes.aggregate();
 
@@ -123,6 +123,4 @@ pub fn bench<const PRETTY: bool>(args: &[&str]) {
     } else {
         println!("Bench: {nodes} nodes {nps:.0} nps");
     }
-
-    crate::misc::dbg_print();
 }
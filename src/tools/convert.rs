@@ -0,0 +1,96 @@
+//! Converts training data between the text format, where every line is
+//! `<fen> | <score> | <result>`, and the binary format of [`PackedBoard`] records.
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Read, Write},
+};
+
+use crate::board::{Board, PackedBoard};
+
+pub fn convert(args: &[&str]) {
+    let [input, output, format] = args else {
+        eprintln!("Usage: convert <input> <output> <text|binary>");
+        return;
+    };
+
+    let result = match *format {
+        "binary" => to_binary(input, output),
+        "text" => to_text(input, output),
+        _ => {
+            eprintln!("Unknown format: '{format}'");
+            return;
+        }
+    };
+
+    match result {
+        Ok(count) => println!("Converted {count} positions to {output}"),
+        Err(error) => eprintln!("Failed to convert '{input}': {error}"),
+    }
+}
+
+fn to_binary(input: &str, output: &str) -> std::io::Result<usize> {
+    let reader = BufReader::new(File::open(input)?);
+    let mut writer = BufWriter::new(File::create(output)?);
+    let mut count = 0;
+
+    for (line_number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let Some(packed) = parse_line(&line) else {
+            eprintln!("Skipping invalid entry on line {}: '{line}'", line_number + 1);
+            continue;
+        };
+
+        writer.write_all(&packed.to_bytes())?;
+        count += 1;
+    }
+
+    writer.flush()?;
+    Ok(count)
+}
+
+fn to_text(input: &str, output: &str) -> std::io::Result<usize> {
+    let mut reader = BufReader::new(File::open(input)?);
+    let mut writer = BufWriter::new(File::create(output)?);
+    let mut bytes = [0; PackedBoard::SIZE];
+    let mut count = 0;
+
+    loop {
+        match reader.read_exact(&mut bytes) {
+            Ok(()) => (),
+            Err(error) if error.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(error) => return Err(error),
+        }
+
+        let packed = PackedBoard::from_bytes(&bytes);
+        let Some(board) = packed.unpack() else {
+            eprintln!("Skipping malformed record {}", count + 1);
+            continue;
+        };
+
+        writeln!(writer, "{} | {} | {:.1}", board.to_fen(), packed.score, packed.result as f32 / 2.0)?;
+        count += 1;
+    }
+
+    writer.flush()?;
+    Ok(count)
+}
+
+fn parse_line(line: &str) -> Option<PackedBoard> {
+    let mut parts = line.split('|').map(str::trim);
+
+    let board = Board::from_fen(parts.next()?).ok()?;
+    let score = parts.next()?.parse().ok()?;
+    let result = match parts.next()? {
+        "1.0" | "1" => 2,
+        "0.5" => 1,
+        "0.0" | "0" => 0,
+        _ => return None,
+    };
+
+    Some(PackedBoard::pack(&board, score, result))
+}
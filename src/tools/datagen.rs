@@ -0,0 +1,271 @@
+//! Generates training data for the network by playing self-play games.
+//!
+//! Every game starts from a few random plies, after which each move is chosen by a
+//! fixed-nodes search. Quiet positions are recorded together with the search score
+//! and, once the game is over, its result. Games are adjudicated early when both
+//! sides agree on a decisive or drawn score, or when the position is in the tablebases.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    board::{Board, NullBoardObserver, PackedBoard},
+    random::Random,
+    search::Report,
+    thread::SharedContext,
+    threadpool::ThreadPool,
+    time::{Limits, TimeManager},
+    types::{Color, Move, is_decisive, normalize_to_cp},
+};
+
+const DEFAULT_GAMES: usize = 1000;
+const DEFAULT_THREADS: usize = 1;
+const DEFAULT_NODES: u64 = 5000;
+const HASH_PER_THREAD: usize = 16;
+
+const RANDOM_PLIES: usize = 8;
+const MAX_OPENING_SCORE: i32 = 1000;
+
+// Opening and adjudication thresholds are in internal score units, not centipawns
+const WIN_SCORE: i32 = 2500;
+const WIN_PLIES: usize = 4;
+const DRAW_SCORE: i32 = 10;
+const DRAW_PLIES: usize = 12;
+const DRAW_MIN_PLY: usize = 80;
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum Format {
+    Text,
+    Binary,
+}
+
+struct Sample {
+    fen: String,
+    packed: PackedBoard,
+}
+
+pub fn datagen(args: &[&str]) {
+    let Some(path) = args.first() else {
+        eprintln!("Usage: datagen <output> [games] [threads] [nodes] [text|binary]");
+        return;
+    };
+
+    let games = args.get(1).and_then(|v| v.parse().ok()).unwrap_or(DEFAULT_GAMES);
+    let threads = args.get(2).and_then(|v| v.parse().ok()).unwrap_or(DEFAULT_THREADS).max(1);
+    let nodes = args.get(3).and_then(|v| v.parse().ok()).unwrap_or(DEFAULT_NODES);
+    let format = match args.get(4).copied() {
+        Some("binary") => Format::Binary,
+        Some("text") | None => Format::Text,
+        Some(format) => {
+            eprintln!("Unknown format: '{format}'");
+            return;
+        }
+    };
+
+    let output = match File::create(path) {
+        Ok(file) => Mutex::new(BufWriter::new(file)),
+        Err(error) => {
+            eprintln!("Failed to create '{path}': {error}");
+            return;
+        }
+    };
+
+    let seed = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_nanos() as u64);
+
+    println!("Games:   {games}");
+    println!("Threads: {threads}");
+    println!("Nodes:   {nodes}");
+    println!("Seed:    {seed}");
+
+    let played = AtomicUsize::new(0);
+    let positions = AtomicUsize::new(0);
+    let time = Instant::now();
+
+    std::thread::scope(|scope| {
+        for id in 0..threads {
+            let output = &output;
+            let played = &played;
+            let positions = &positions;
+
+            scope.spawn(move || {
+                let mut rng = Random::new(seed ^ (id as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+                let mut worker = Worker::new(nodes);
+
+                while played.fetch_add(1, Ordering::Relaxed) < games {
+                    let (samples, result) = worker.play_game(&mut rng);
+                    let bytes = encode(&samples, result, format);
+
+                    output.lock().unwrap().write_all(&bytes).expect("failed to write training data");
+
+                    let count = positions.fetch_add(samples.len(), Ordering::Relaxed) + samples.len();
+                    let seconds = time.elapsed().as_secs_f64();
+                    eprint!("Positions: {count} ({:.0} pos/s)\r", count as f64 / seconds);
+                }
+            });
+        }
+    });
+
+    output.into_inner().unwrap().flush().expect("failed to write training data");
+
+    eprintln!();
+    println!("Wrote {} positions to {path} in {:.1}s", positions.into_inner(), time.elapsed().as_secs_f64());
+}
+
+/// Encodes the samples of a single game, with the result given from white's perspective.
+fn encode(samples: &[Sample], result: f32, format: Format) -> Vec<u8> {
+    let mut bytes = Vec::new();
+
+    for sample in samples {
+        match format {
+            Format::Text => {
+                let line = format!("{} | {} | {result:.1}\n", sample.fen, sample.packed.score);
+                bytes.extend_from_slice(line.as_bytes());
+            }
+            Format::Binary => {
+                let mut packed = sample.packed;
+                packed.result = (2.0 * result) as u8;
+                bytes.extend_from_slice(&packed.to_bytes());
+            }
+        }
+    }
+
+    bytes
+}
+
+struct Worker {
+    shared: Arc<SharedContext>,
+    pool: ThreadPool,
+    nodes: u64,
+}
+
+impl Worker {
+    fn new(nodes: u64) -> Self {
+        let shared = Arc::new(SharedContext::default());
+        shared.tt.resize(1, HASH_PER_THREAD);
+
+        let pool = ThreadPool::new(shared.clone());
+        Self { shared, pool, nodes }
+    }
+
+    /// Searches the position and returns the best move with its score from white's perspective.
+    fn search(&mut self, board: &Board) -> (Move, i32) {
+        let limits = Limits { nodes: Some(self.nodes), ..Default::default() };
+        let time_manager = TimeManager::new(limits, board.fullmove_number(), 0);
+
+        self.pool.execute_searches(time_manager, Report::None, 1, None, board, &self.shared);
+
+        let root_move = &self.pool.main_thread().root_moves[0];
+        let score = if board.side_to_move() == Color::White { root_move.score } else { -root_move.score };
+        (root_move.mv, score)
+    }
+
+    /// Plays a position reached by random moves, retrying until the position is balanced.
+    fn opening(&mut self, rng: &mut Random) -> Board {
+        'retry: loop {
+            let mut board = Board::starting_position();
+
+            for _ in 0..RANDOM_PLIES + rng.below(2) as usize {
+                let moves = board.generate_all_moves();
+                if moves.is_empty() {
+                    continue 'retry;
+                }
+
+                let mv = moves[rng.below(moves.len() as u64) as usize].mv;
+                board.make_move(mv, &mut NullBoardObserver);
+            }
+
+            if !board.has_legal_moves() {
+                continue;
+            }
+
+            if self.search(&board).1.abs() <= MAX_OPENING_SCORE {
+                return board;
+            }
+        }
+    }
+
+    /// Plays a single game and returns the recorded samples with the result for white.
+    fn play_game(&mut self, rng: &mut Random) -> (Vec<Sample>, f32) {
+        self.pool.clear();
+        self.shared.tt.clear(1);
+
+        let mut board = self.opening(rng);
+        let mut samples = Vec::new();
+
+        let mut win_plies = 0;
+        let mut loss_plies = 0;
+        let mut draw_plies = 0;
+
+        let result = loop {
+            if !board.has_legal_moves() {
+                break match board.in_check() {
+                    true if board.side_to_move() == Color::White => 0.0,
+                    true => 1.0,
+                    false => 0.5,
+                };
+            }
+
+            if board.is_draw(0) {
+                break 0.5;
+            }
+
+            #[cfg(feature = "syzygy")]
+            if board.occupancies().popcount() <= crate::tb::size()
+                && let Some(outcome) = crate::tb::probe(&board)
+            {
+                let white = board.side_to_move() == Color::White;
+                break match outcome {
+                    crate::tb::GameOutcome::Win => {
+                        if white {
+                            1.0
+                        } else {
+                            0.0
+                        }
+                    }
+                    crate::tb::GameOutcome::Loss => {
+                        if white {
+                            0.0
+                        } else {
+                            1.0
+                        }
+                    }
+                    crate::tb::GameOutcome::Draw => 0.5,
+                };
+            }
+
+            let (mv, score) = self.search(&board);
+
+            win_plies = if score >= WIN_SCORE { win_plies + 1 } else { 0 };
+            loss_plies = if score <= -WIN_SCORE { loss_plies + 1 } else { 0 };
+            draw_plies = if score.abs() <= DRAW_SCORE { draw_plies + 1 } else { 0 };
+
+            if win_plies >= WIN_PLIES {
+                break 1.0;
+            }
+            if loss_plies >= WIN_PLIES {
+                break 0.0;
+            }
+            if draw_plies >= DRAW_PLIES && board.fullmove_number() * 2 >= DRAW_MIN_PLY {
+                break 0.5;
+            }
+
+            if !board.in_check() && !mv.is_noisy() && !is_decisive(score) {
+                samples.push(Sample {
+                    fen: board.to_fen(),
+                    packed: PackedBoard::pack(&board, normalize_to_cp(score, &board) as i16, 0),
+                });
+            }
+
+            board.make_move(mv, &mut NullBoardObserver);
+        };
+
+        (samples, result)
+    }
+}
@@ -0,0 +1,231 @@
+//! Runs a test suite stored in [Extended Position Description][epd] format,
+//! such as WAC or STS, and checks the best move found for every position
+//! against the `bm` (best move), `am` (avoid move) and `dm` (direct mate) opcodes.
+//!
+//! [epd]: https://www.chessprogramming.org/Extended_Position_Description
+
+use std::{sync::Arc, time::Instant};
+
+use crate::{
+    board::Board,
+    search::Report,
+    thread::SharedContext,
+    threadpool::ThreadPool,
+    time::TimeManager,
+    types::{Move, Score},
+    uci::parse_limits,
+};
+
+/// Limits used when the command does not specify any, so that a suite never runs unbounded.
+const DEFAULT_LIMITS: [&str; 2] = ["depth", "10"];
+
+struct Entry {
+    id: String,
+    board: Board,
+    best_moves: Vec<Move>,
+    avoid_moves: Vec<Move>,
+    direct_mate: Option<i32>,
+}
+
+impl Entry {
+    fn parse(line: &str) -> Option<Self> {
+        let fields = line.split_whitespace().take(4).collect::<Vec<_>>();
+        if fields.len() < 4 {
+            return None;
+        }
+
+        let board = Board::from_fen(&fields.join(" ")).ok()?;
+
+        // Skip the four FEN fields, preserving the remainder of the line verbatim
+        let mut operations = line.trim_start();
+        for _ in 0..4 {
+            operations = operations.split_once(char::is_whitespace).map_or("", |(_, rest)| rest).trim_start();
+        }
+
+        let mut entry = Self {
+            id: String::new(),
+            board,
+            best_moves: Vec::new(),
+            avoid_moves: Vec::new(),
+            direct_mate: None,
+        };
+
+        for operation in split_operations(operations) {
+            let (opcode, operands) = operation.split_once(char::is_whitespace).unwrap_or((operation, ""));
+            let operands = operands.trim();
+
+            match opcode {
+                "id" => entry.id = operands.trim_matches('"').to_string(),
+                "bm" => entry.best_moves = entry.parse_moves(operands)?,
+                "am" => entry.avoid_moves = entry.parse_moves(operands)?,
+                "dm" => entry.direct_mate = operands.parse().ok(),
+                _ => (),
+            }
+        }
+
+        Some(entry)
+    }
+
+    fn parse_moves(&self, operands: &str) -> Option<Vec<Move>> {
+        operands
+            .split_whitespace()
+            .map(|operand| {
+                let moves = self.board.generate_all_moves();
+                let uci = moves.iter().map(|entry| entry.mv).find(|mv| mv.to_uci(&self.board) == operand);
+                uci.or_else(|| self.board.parse_san(operand))
+            })
+            .collect()
+    }
+
+    fn is_solved(&self, mv: Move, score: i32) -> bool {
+        if !self.best_moves.is_empty() && !self.best_moves.contains(&mv) {
+            return false;
+        }
+
+        if self.avoid_moves.contains(&mv) {
+            return false;
+        }
+
+        match self.direct_mate {
+            Some(moves) => score >= Score::MATE_IN_MAX && (Score::MATE - score + 1) / 2 <= moves,
+            None => true,
+        }
+    }
+
+    fn expected(&self) -> String {
+        let format = |moves: &[Move]| moves.iter().map(|mv| mv.to_san(&self.board)).collect::<Vec<_>>().join(" ");
+
+        let mut expected = Vec::new();
+        if !self.best_moves.is_empty() {
+            expected.push(format!("bm {}", format(&self.best_moves)));
+        }
+        if !self.avoid_moves.is_empty() {
+            expected.push(format!("am {}", format(&self.avoid_moves)));
+        }
+        if let Some(moves) = self.direct_mate {
+            expected.push(format!("dm {moves}"));
+        }
+        expected.join("; ")
+    }
+}
+
+/// Splits EPD operations on semicolons that are not enclosed in quotes.
+fn split_operations(operations: &str) -> Vec<&str> {
+    let mut result = Vec::new();
+    let mut quoted = false;
+    let mut start = 0;
+
+    for (index, c) in operations.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            ';' if !quoted => {
+                result.push(operations[start..index].trim());
+                start = index + 1;
+            }
+            _ => (),
+        }
+    }
+
+    result.push(operations[start..].trim());
+    result.retain(|operation| !operation.is_empty());
+    result
+}
+
+pub fn epd(pool: &mut ThreadPool, shared: &Arc<SharedContext>, args: &[&str]) {
+    let [path, limits @ ..] = args else {
+        eprintln!("Usage: epd <file> [depth <n>] [nodes <n>] [movetime <ms>]");
+        return;
+    };
+
+    let limits = if limits.is_empty() || limits.contains(&"infinite") { &DEFAULT_LIMITS[..] } else { limits };
+
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(error) => {
+            eprintln!("Failed to read '{path}': {error}");
+            return;
+        }
+    };
+
+    let mut solved = 0;
+    let mut total = 0;
+    let mut nodes = 0;
+
+    let time = Instant::now();
+
+    for (line_number, line) in text.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let Some(entry) = Entry::parse(line) else {
+            eprintln!("Skipping invalid EPD on line {}: '{line}'", line_number + 1);
+            continue;
+        };
+
+        pool.clear();
+        shared.tt.clear(pool.len());
+
+        let time_manager = TimeManager::new(parse_limits(entry.board.side_to_move(), limits), 0, 0);
+        pool.execute_searches(time_manager, Report::None, 1, None, &entry.board, shared);
+
+        let (mv, score) = match pool.main_thread().root_moves.first() {
+            Some(root_move) => (root_move.mv, root_move.score),
+            None => (Move::NULL, -Score::INFINITE),
+        };
+        let is_solved = mv.is_present() && entry.is_solved(mv, score);
+
+        total += 1;
+        solved += is_solved as usize;
+        nodes += shared.nodes.aggregate();
+
+        let id = if entry.id.is_empty() { format!("#{}", line_number + 1) } else { entry.id.clone() };
+
+        println!(
+            "{:<16} {:<6} {:<8} {}",
+            id,
+            if is_solved { "solved" } else { "failed" },
+            if mv.is_present() { mv.to_san(&entry.board) } else { "(none)".to_string() },
+            entry.expected()
+        );
+    }
+
+    let seconds = time.elapsed().as_secs_f64();
+
+    println!("{}", "-".repeat(50));
+    println!("Solved: {solved}/{total} ({nodes} nodes, {seconds:.3}s)");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_operations() {
+        assert_eq!(split_operations("bm Nf3; id \"a; b\";"), ["bm Nf3", "id \"a; b\""]);
+        assert_eq!(split_operations(" ;; am e4 ; "), ["am e4"]);
+        assert!(split_operations("").is_empty());
+    }
+
+    #[test]
+    fn test_entry_parse() {
+        let entry = Entry::parse(
+            "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - bm Bb5 d2d4; am Ke2; id \"WAC; 1\";",
+        )
+        .unwrap();
+
+        assert_eq!(entry.id, "WAC; 1");
+        assert_eq!(entry.best_moves.iter().map(|mv| mv.to_uci(&entry.board)).collect::<Vec<_>>(), ["f1b5", "d2d4"]);
+        assert_eq!(entry.avoid_moves.iter().map(|mv| mv.to_uci(&entry.board)).collect::<Vec<_>>(), ["e1e2"]);
+        assert_eq!(entry.direct_mate, None);
+        assert_eq!(entry.expected(), "bm Bb5 d4; am Ke2");
+
+        let entry = Entry::parse("6k1/5ppp/8/8/8/8/8/R5K1 w - - dm 1;").unwrap();
+        assert_eq!(entry.direct_mate, Some(1));
+        assert!(entry.is_solved(entry.board.parse_san("Ra8").unwrap(), Score::MATE - 1));
+        assert!(!entry.is_solved(entry.board.parse_san("Ra8").unwrap(), 100));
+
+        assert!(Entry::parse("8/8/8/8 w - -").is_none());
+        assert!(Entry::parse("6k1/8/8/8/8/8/8/R5K1 w - - bm Ra9;").is_none());
+    }
+}
@@ -1,9 +1,19 @@
 mod bench;
+mod convert;
+mod datagen;
+mod epd;
 mod perft;
 mod speedtest;
+mod traceview;
 
 pub use bench::bench;
+pub use convert::convert;
+pub use datagen::datagen;
+pub use epd::epd;
 pub use perft::is_legal_perft;
+pub use perft::parallel_perft;
 pub use perft::perft;
+pub use perft::perft_suite;
 pub use perft::simple_perft;
 pub use speedtest::speedtest;
+pub use traceview::traceview;
@@ -3,13 +3,63 @@
 //!
 //! See [Perft](https://www.chessprogramming.org/Perft) for more information.
 
-use std::time::Instant;
+use std::{
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+    time::Instant,
+};
 
 use crate::{
-    board::{Board, NullBoardObserver},
+    board::{Board, NullBoardObserver, ParseFenError},
+    threadpool::ThreadPool,
     types::{Move, MoveList},
 };
 
+/// Subtree sizes shared between the threads of a parallel perft, indexed by position hash.
+///
+/// Each entry stores its key XOR-ed with the data, so that an entry torn by concurrent
+/// writes fails verification instead of returning a wrong count.
+struct PerftHash {
+    entries: Vec<PerftEntry>,
+}
+
+#[derive(Default)]
+struct PerftEntry {
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+impl PerftHash {
+    fn new(megabytes: usize) -> Self {
+        let len = (megabytes * 1024 * 1024 / size_of::<PerftEntry>()).max(1);
+        Self { entries: (0..len).map(|_| PerftEntry::default()).collect() }
+    }
+
+    fn entry(&self, hash: u64) -> &PerftEntry {
+        &self.entries[((hash as u128 * self.entries.len() as u128) >> 64) as usize]
+    }
+
+    fn probe(&self, hash: u64, depth: usize) -> Option<u64> {
+        let entry = self.entry(hash);
+        let data = entry.data.load(Ordering::Relaxed);
+        let key = entry.key.load(Ordering::Relaxed) ^ data;
+
+        (key == hash && data & 0xFF == depth as u64).then_some(data >> 8)
+    }
+
+    /// Stores the node count of a subtree, unless it doesn't fit in the 56 bits left beside the depth.
+    fn store(&self, hash: u64, depth: usize, nodes: u64) {
+        if nodes >> 56 != 0 {
+            return;
+        }
+
+        let entry = self.entry(hash);
+        let data = (nodes << 8) | depth as u64;
+
+        entry.key.store(hash ^ data, Ordering::Relaxed);
+        entry.data.store(data, Ordering::Relaxed);
+    }
+}
+
 pub fn perft(depth: usize, board: &mut Board) {
     println!("{}", "-".repeat(60));
     println!("{:>12} {:>12} {:>13} {:>15}", "Move", "Nodes", "Elapsed", "NPS");
@@ -66,6 +116,165 @@ pub fn simple_perft(depth: usize, board: &mut Board) {
     println!("total: {nodes}");
 }
 
+/// Splits the root moves across the threads of the pool, optionally sharing a hash table
+/// of `hash_size` MB between them. The output has the same format as `simple_perft`.
+pub fn parallel_perft(pool: &ThreadPool, board: &Board, args: &[&str]) {
+    let Some(depth) = args.first().and_then(|depth| depth.parse::<usize>().ok()) else {
+        eprintln!("Usage: parallelperft <depth> [hash]");
+        return;
+    };
+
+    // The root is the only leaf at depth 0, so there are no root moves to split
+    if depth == 0 {
+        println!("total: 1");
+        return;
+    }
+
+    let hash_size = args.get(1).and_then(|size| size.parse().ok()).unwrap_or(0);
+    let hash = (hash_size > 0).then(|| PerftHash::new(hash_size));
+    print_divide(board, &divide(depth, pool, board, hash.as_ref()));
+}
+
+/// Runs the perft suite in `<fen> ;D1 <nodes> ;D2 <nodes> ...` format from a file,
+/// printing a divide at the first depth of each position that doesn't match.
+pub fn perft_suite(pool: &ThreadPool, args: &[&str]) {
+    const HASH_SIZE: usize = 64;
+
+    let [path, rest @ ..] = args else {
+        eprintln!("Usage: perftsuite <file> [max depth]");
+        return;
+    };
+
+    let max_depth = rest.first().and_then(|depth| depth.parse().ok()).unwrap_or(usize::MAX);
+
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(error) => {
+            eprintln!("Failed to read '{path}': {error}");
+            return;
+        }
+    };
+
+    let hash = PerftHash::new(HASH_SIZE);
+    let now = Instant::now();
+
+    let mut passed = 0;
+    let mut total = 0;
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        total += 1;
+
+        let Some((fen, depths)) = parse_suite_line(line) else {
+            eprintln!("Invalid perft suite entry on line {}", number + 1);
+            continue;
+        };
+
+        let board = match suite_board(fen) {
+            Ok(board) => board,
+            Err(error) => {
+                eprintln!("Invalid FEN on line {}: {error}", number + 1);
+                continue;
+            }
+        };
+
+        match check_suite_entry(&board, &depths, max_depth, pool, &hash) {
+            None => {
+                passed += 1;
+                println!("{fen}: ok");
+            }
+            Some(Mismatch { depth, expected, nodes, divide }) => {
+                println!("{fen}: depth {depth} expected {expected}, found {nodes}");
+                print_divide(&board, &divide);
+            }
+        }
+    }
+
+    println!("Passed {passed} of {total} positions in {:.3}s", now.elapsed().as_secs_f64());
+}
+
+/// Parses a perft suite entry such as `<fen> ;D1 20 ;D2 400`, returning the FEN and the depths with their node counts.
+fn parse_suite_line(line: &str) -> Option<(&str, Vec<(usize, u64)>)> {
+    let mut fields = line.split(';');
+    let fen = fields.next()?.trim();
+
+    let depths = fields
+        .map(|field| {
+            let (depth, nodes) = field.trim().strip_prefix('D')?.split_once(' ')?;
+            Some((depth.parse().ok()?, nodes.trim().parse().ok()?))
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    (!fen.is_empty()).then_some((fen, depths))
+}
+
+/// Sets up the position of a perft suite entry, in Chess960 mode if its castling rights require it.
+fn suite_board(fen: &str) -> Result<Board, ParseFenError> {
+    let mut board = Board::from_fen_strict(fen)?;
+    board.set_frc(Board::is_frc_fen(fen));
+    Ok(board)
+}
+
+/// The first depth of a perft suite entry whose node count doesn't match.
+struct Mismatch {
+    depth: usize,
+    expected: u64,
+    nodes: u64,
+    divide: Vec<(Move, u64)>,
+}
+
+/// Compares the node counts of a perft suite entry up to `max_depth`, returning the first mismatch.
+fn check_suite_entry(
+    board: &Board, depths: &[(usize, u64)], max_depth: usize, pool: &ThreadPool, hash: &PerftHash,
+) -> Option<Mismatch> {
+    depths.iter().filter(|&&(depth, _)| (1..=max_depth).contains(&depth)).find_map(|&(depth, expected)| {
+        let divide = divide(depth, pool, board, Some(hash));
+        let nodes = divide.iter().map(|&(_, count)| count).sum::<u64>();
+        (nodes != expected).then_some(Mismatch { depth, expected, nodes, divide })
+    })
+}
+
+/// Counts the leaf nodes below each root move, splitting the root moves across the threads of the pool.
+fn divide(depth: usize, pool: &ThreadPool, board: &Board, hash: Option<&PerftHash>) -> Vec<(Move, u64)> {
+    debug_assert!(depth > 0);
+
+    let moves = board.generate_all_moves();
+
+    let next = AtomicUsize::new(0);
+    let counts = (0..moves.len()).map(|_| AtomicU64::new(0)).collect::<Vec<_>>();
+
+    pool.broadcast(|_| {
+        let mut board = board.clone();
+
+        loop {
+            let index = next.fetch_add(1, Ordering::Relaxed);
+            if index >= moves.len() {
+                break;
+            }
+
+            let mv = moves[index].mv;
+
+            board.make_move(mv, &mut NullBoardObserver);
+            counts[index].store(perft_hashed(depth - 1, &mut board, hash), Ordering::Relaxed);
+            board.undo_move(mv);
+        }
+    });
+
+    moves.iter().zip(counts).map(|(entry, count)| (entry.mv, count.into_inner())).collect()
+}
+
+fn print_divide(board: &Board, divide: &[(Move, u64)]) {
+    for &(mv, count) in divide {
+        println!("{}: {count}", mv.to_uci(board));
+    }
+
+    println!("total: {}", divide.iter().map(|&(_, count)| count).sum::<u64>());
+}
+
 pub fn is_legal_perft(depth: usize, board: &mut Board) {
     let mut nodes = 0;
 
@@ -106,6 +315,31 @@ fn perft_internal<F: Fn(&Board) -> MoveList>(move_gen: &F, depth: usize, board:
     nodes
 }
 
+fn perft_hashed(depth: usize, board: &mut Board, hash: Option<&PerftHash>) -> u64 {
+    if depth <= 1 {
+        return perft_internal(&|board| board.generate_all_moves(), depth, board);
+    }
+
+    if let Some(nodes) = hash.and_then(|hash| hash.probe(board.hash(), depth)) {
+        return nodes;
+    }
+
+    let mut nodes = 0;
+
+    for entry in board.generate_all_moves().iter() {
+        let mv = entry.mv;
+        board.make_move(mv, &mut NullBoardObserver);
+        nodes += perft_hashed(depth - 1, board, hash);
+        board.undo_move(mv);
+    }
+
+    if let Some(hash) = hash {
+        hash.store(board.hash(), depth, nodes);
+    }
+
+    nodes
+}
+
 fn is_legal_movegen(board: &Board) -> MoveList {
     let mut moves = MoveList::new();
     for i in 0..=u16::MAX {
@@ -120,3 +354,61 @@ fn is_legal_movegen(board: &Board) -> MoveList {
     }
     moves
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::thread::SharedContext;
+
+    #[test]
+    fn test_divide() {
+        let mut pool = ThreadPool::new(Arc::new(SharedContext::default()));
+        pool.set_count(2);
+
+        let board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+        let hash = PerftHash::new(1);
+
+        for (depth, expected) in [(1, 48), (2, 2039), (3, 97862), (4, 4085603)] {
+            let plain = divide(depth, &pool, &board, None);
+            let hashed = divide(depth, &pool, &board, Some(&hash));
+
+            assert_eq!(plain.len(), 48);
+            assert_eq!(plain, hashed);
+            assert_eq!(plain.iter().map(|&(_, count)| count).sum::<u64>(), expected);
+        }
+
+        // Depth 0 has no root moves to divide
+        assert!(check_suite_entry(&board, &[(0, 1), (1, 48)], usize::MAX, &pool, &hash).is_none());
+    }
+
+    #[test]
+    fn test_hash_bounds() {
+        let hash = PerftHash::new(1);
+
+        hash.store(0x1234, 5, (1 << 56) - 1);
+        assert_eq!(hash.probe(0x1234, 5), Some((1 << 56) - 1));
+        assert_eq!(hash.probe(0x1234, 4), None);
+
+        hash.store(0x5678, 5, 1 << 56);
+        assert_eq!(hash.probe(0x5678, 5), None);
+    }
+
+    #[test]
+    #[ignore = "runs deep perft on every position, use `cargo test -- --ignored`"]
+    fn test_perft_suite() {
+        let mut pool = ThreadPool::new(Arc::new(SharedContext::default()));
+        pool.set_count(std::thread::available_parallelism().map_or(1, |threads| threads.get()));
+
+        let hash = PerftHash::new(64);
+
+        for line in include_str!("../board/perftsuite.epd").lines().filter(|line| !line.trim().is_empty()) {
+            let (fen, depths) = parse_suite_line(line).unwrap();
+            let board = suite_board(fen).unwrap();
+
+            let mismatch = check_suite_entry(&board, &depths, usize::MAX, &pool, &hash);
+            assert_eq!(mismatch.map(|m| (m.depth, m.expected, m.nodes)), None, "{fen}");
+        }
+    }
+}
@@ -92,8 +92,8 @@ pub fn speedtest(args: &[&str]) {
         eprint!("Warmup {} of {}\r", index + 1, WARMUP_POSITIONS_COUNT);
 
         let board = Board::from_fen(position).unwrap();
-        let time_manager = TimeManager::new(Limits::Time(ms_per_position), 0, 0);
-        pool.execute_searches(time_manager, Report::None, 1, &board, &shared);
+        let time_manager = TimeManager::new(Limits::time(ms_per_position), 0, 0);
+        pool.execute_searches(time_manager, Report::None, 1, None, &board, &shared);
     }
     eprintln!();
 
@@ -104,8 +104,8 @@ pub fn speedtest(args: &[&str]) {
         eprint!("Position {} of {}\r", index + 1, POSITIONS.len());
 
         let board = Board::from_fen(position).unwrap();
-        let time_manager = TimeManager::new(Limits::Time(ms_per_position), 0, 0);
-        pool.execute_searches(time_manager, Report::None, 1, &board, &shared);
+        let time_manager = TimeManager::new(Limits::time(ms_per_position), 0, 0);
+        pool.execute_searches(time_manager, Report::None, 1, None, &board, &shared);
 
         nodes += shared.nodes.aggregate();
     }
@@ -117,6 +117,4 @@ pub fn speedtest(args: &[&str]) {
     println!("Total nodes:       {nodes}");
     println!("Total time (sec):  {seconds:.2}");
     println!("Nodes/second:      {}", nps as u64);
-
-    crate::misc::dbg_print();
 }
@@ -0,0 +1,102 @@
+//! Browses a search tree written by the `trace` feature.
+//!
+//! The path selects a node by descending from the top level, where every step is either
+//! the index of a child or a move, which picks the last child reached by that move.
+//! The selected line is printed followed by the children of its last node.
+
+struct Node {
+    text: String,
+    children: Vec<usize>,
+    size: usize,
+}
+
+pub fn traceview(args: &[&str]) {
+    let [path, steps @ ..] = args else {
+        eprintln!("Usage: traceview <file> [index|move ...]");
+        return;
+    };
+
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(error) => {
+            eprintln!("Failed to read '{path}': {error}");
+            return;
+        }
+    };
+
+    let mut lines = text.lines().peekable();
+    if let Some(fen) = lines.next_if(|line| line.starts_with('#')) {
+        println!("Position: {}", fen.trim_start_matches('#').trim());
+    }
+
+    let (nodes, roots) = parse(lines);
+
+    let mut line = Vec::new();
+    let mut children = &roots;
+
+    for step in steps {
+        let child = match step.parse::<usize>() {
+            Ok(index) => children.get(index).copied(),
+            Err(_) => {
+                children.iter().rev().copied().find(|&child| nodes[child].text.split_whitespace().next() == Some(*step))
+            }
+        };
+
+        let Some(child) = child else {
+            eprintln!("No child '{step}' after {} steps", line.len());
+            return;
+        };
+
+        line.push(child);
+        children = &nodes[child].children;
+    }
+
+    for (level, &node) in line.iter().enumerate() {
+        println!("{:indent$}{}", "", nodes[node].text, indent = 2 * level);
+    }
+
+    for (index, &child) in children.iter().enumerate() {
+        println!(
+            "{:indent$}[{index}] {} ({} nodes)",
+            "",
+            nodes[child].text,
+            nodes[child].size,
+            indent = 2 * line.len()
+        );
+    }
+}
+
+/// Builds the tree from lines indented by two spaces per level, returning the nodes and the top level.
+fn parse<'a>(lines: impl Iterator<Item = &'a str>) -> (Vec<Node>, Vec<usize>) {
+    let mut nodes: Vec<Node> = Vec::new();
+    let mut roots = Vec::new();
+    let mut open: Vec<usize> = Vec::new();
+
+    for line in lines {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        let level = (line.len() - trimmed.len()) / 2;
+        while open.len() > level {
+            open.pop();
+        }
+
+        let index = nodes.len();
+        nodes.push(Node { text: trimmed.to_string(), children: Vec::new(), size: 1 });
+
+        match open.last() {
+            Some(&parent) => nodes[parent].children.push(index),
+            None => roots.push(index),
+        }
+
+        for &ancestor in &open {
+            nodes[ancestor].size += 1;
+        }
+
+        open.push(index);
+    }
+
+    (nodes, roots)
+}
@@ -0,0 +1,152 @@
+//! Records the search tree of the main thread for offline inspection.
+//!
+//! Every node entered by `search` or `qsearch` is stored together with its window, depth,
+//! static evaluation, the pruning decision that ended it (if any) and the returned score,
+//! until the node or ply budget is exhausted. At the end of the search the tree is written
+//! to a file, one node per line indented by its nesting level, which can be browsed with
+//! the `traceview` command.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use crate::{
+    board::Board,
+    types::{Move, Score},
+};
+
+pub const DEFAULT_TRACE_NODES: usize = 1_000_000;
+pub const DEFAULT_TRACE_PLY: isize = 8;
+
+struct Node {
+    level: usize,
+    ply: isize,
+    mv: Move,
+    qsearch: bool,
+    alpha: i32,
+    beta: i32,
+    depth: i32,
+    eval: i32,
+    score: i32,
+    decision: &'static str,
+}
+
+pub struct Tracer {
+    path: Option<String>,
+    max_nodes: usize,
+    max_ply: isize,
+    nodes: Vec<Node>,
+    open: Vec<Option<usize>>,
+}
+
+impl Default for Tracer {
+    fn default() -> Self {
+        Self {
+            path: None,
+            max_nodes: DEFAULT_TRACE_NODES,
+            max_ply: DEFAULT_TRACE_PLY,
+            nodes: Vec::new(),
+            open: Vec::new(),
+        }
+    }
+}
+
+impl Tracer {
+    pub fn configure(&mut self, path: Option<String>, max_nodes: usize, max_ply: isize) {
+        self.path = path;
+        self.max_nodes = max_nodes;
+        self.max_ply = max_ply;
+    }
+
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.open.clear();
+    }
+
+    pub fn enter(&mut self, ply: isize, mv: Move, alpha: i32, beta: i32, depth: i32, qsearch: bool) {
+        let parent_recorded = self.open.last().is_none_or(Option::is_some);
+
+        let index =
+            (self.path.is_some() && parent_recorded && ply <= self.max_ply && self.nodes.len() < self.max_nodes).then(
+                || {
+                    self.nodes.push(Node {
+                        level: self.open.len(),
+                        ply,
+                        mv,
+                        qsearch,
+                        alpha,
+                        beta,
+                        depth,
+                        eval: Score::NONE,
+                        score: Score::NONE,
+                        decision: "",
+                    });
+                    self.nodes.len() - 1
+                },
+            );
+
+        self.open.push(index);
+    }
+
+    pub fn exit(&mut self, score: i32) {
+        if let Some(Some(index)) = self.open.pop() {
+            self.nodes[index].score = score;
+        }
+    }
+
+    /// Records the static evaluation of the node being searched.
+    pub fn eval(&mut self, eval: i32) {
+        if let Some(&Some(index)) = self.open.last() {
+            self.nodes[index].eval = eval;
+        }
+    }
+
+    /// Records the reason the node being searched returned early.
+    pub fn decision(&mut self, decision: &'static str) {
+        if let Some(&Some(index)) = self.open.last() {
+            self.nodes[index].decision = decision;
+        }
+    }
+
+    /// Writes the recorded tree to the configured file, if any.
+    pub fn finish(&mut self, board: &Board) -> std::io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "# {}", board.to_fen())?;
+
+        let format = |score: i32| if score == Score::NONE { "-".to_string() } else { score.to_string() };
+
+        for node in &self.nodes {
+            let mv = if node.mv.is_present() { node.mv.to_uci(board) } else { "null".to_string() };
+            let mv = if node.level == 0 { "root" } else { &mv };
+
+            write!(
+                writer,
+                "{:indent$}{mv} ply={} depth={} alpha={} beta={} eval={} score={}",
+                "",
+                node.ply,
+                node.depth,
+                node.alpha,
+                node.beta,
+                format(node.eval),
+                format(node.score),
+                indent = 2 * node.level
+            )?;
+
+            if node.qsearch {
+                write!(writer, " qs")?;
+            }
+            if !node.decision.is_empty() {
+                write!(writer, " [{}]", node.decision)?;
+            }
+            writeln!(writer)?;
+        }
+
+        self.clear();
+        writer.flush()
+    }
+}
@@ -1,4 +1,9 @@
-use std::sync::atomic::{AtomicPtr, AtomicU8, AtomicUsize, Ordering};
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{BufReader, BufWriter, Error, ErrorKind, Read, Write},
+    sync::atomic::{AtomicPtr, AtomicU8, AtomicUsize, Ordering},
+};
 
 use crate::types::{Move, Score, is_decisive, is_loss, is_valid, is_win};
 
@@ -9,6 +14,10 @@ const CLUSTER_SIZE: usize = std::mem::size_of::<Cluster>();
 
 const ENTRIES_PER_CLUSTER: usize = 3;
 
+const FILE_MAGIC: [u8; 4] = *b"RKTT";
+const FILE_VERSION: u32 = 1;
+const FILE_HEADER_SIZE: usize = 32;
+
 const AGE_CYCLE: u8 = 1 << 5;
 const AGE_MASK: u8 = AGE_CYCLE - 1;
 
@@ -111,6 +120,44 @@ struct Cluster {
 }
 
 impl Cluster {
+    const EMPTY: Self = unsafe { std::mem::zeroed() };
+
+    /// Builds a cluster from the deepest of the given entries, skipping duplicated keys.
+    fn merge(sources: &[Self]) -> Self {
+        if let [source] = sources {
+            return source.clone();
+        }
+
+        let mut candidates = sources
+            .iter()
+            .flat_map(|cluster| {
+                cluster.entries.iter().enumerate().map(move |(index, entry)| (cluster.key(index), entry))
+            })
+            .filter(|(_, entry)| entry.depth() != TtDepth::NONE)
+            .collect::<Vec<_>>();
+
+        candidates.sort_by_key(|(_, entry)| std::cmp::Reverse(entry.depth()));
+
+        let mut cluster = Self::EMPTY;
+        let mut count = 0;
+
+        for (key, entry) in candidates {
+            if count == ENTRIES_PER_CLUSTER {
+                break;
+            }
+
+            if (0..count).any(|index| cluster.key(index) == key) {
+                continue;
+            }
+
+            cluster.entries[count] = entry.clone();
+            cluster.set_key(count, key);
+            count += 1;
+        }
+
+        cluster
+    }
+
     const fn key(&self, index: usize) -> u16 {
         verification_key(self.keys >> (index * 16))
     }
@@ -171,6 +218,73 @@ impl TranspositionTable {
         count / ENTRIES_PER_CLUSTER
     }
 
+    /// Writes the table to `path`, preceded by a header recording its size and age.
+    /// The clusters are stored in native byte order.
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        let mut header = [0; FILE_HEADER_SIZE];
+        header[0..4].copy_from_slice(&FILE_MAGIC);
+        header[4..8].copy_from_slice(&FILE_VERSION.to_le_bytes());
+        header[8..16].copy_from_slice(&(self.len() as u64).to_le_bytes());
+        header[16] = self.age();
+
+        writer.write_all(&header)?;
+        writer.write_all(unsafe { std::slice::from_raw_parts(self.ptr().cast::<u8>(), self.len() * CLUSTER_SIZE) })?;
+        writer.flush()
+    }
+
+    /// Loads a table written by [`save`](Self::save) while keeping the current size.
+    ///
+    /// Since only the verification key of a hash is stored, entries are re-indexed by position:
+    /// every cluster receives the deepest entries of the saved clusters covering the same range
+    /// of hashes. This is exact when the table shrinks by a whole factor; when it grows, the
+    /// entries are replicated across the clusters they may belong to.
+    pub fn load(&self, path: &str) -> std::io::Result<()> {
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let mut header = [0; FILE_HEADER_SIZE];
+        reader.read_exact(&mut header)?;
+
+        if header[0..4] != FILE_MAGIC || header[4..8] != FILE_VERSION.to_le_bytes() {
+            return Err(Error::new(ErrorKind::InvalidData, "not a hash file"));
+        }
+
+        let saved_len = u64::from_le_bytes(header[8..16].try_into().unwrap()) as usize;
+        let file_len = reader.get_ref().metadata()?.len() as usize;
+
+        if saved_len == 0 || file_len != FILE_HEADER_SIZE + saved_len * CLUSTER_SIZE {
+            return Err(Error::new(ErrorKind::InvalidData, "truncated hash file"));
+        }
+
+        let len = self.len();
+        let clusters = unsafe { std::slice::from_raw_parts_mut(self.ptr(), len) };
+
+        let mut window = VecDeque::new();
+        let mut window_start = 0;
+        let mut bytes = [0; CLUSTER_SIZE];
+
+        for (index, cluster) in clusters.iter_mut().enumerate() {
+            let lo = (index as u128 * saved_len as u128 / len as u128) as usize;
+            let hi = ((index as u128 + 1) * saved_len as u128).div_ceil(len as u128) as usize;
+
+            while window_start + window.len() < hi {
+                reader.read_exact(&mut bytes)?;
+                window.push_back(unsafe { std::mem::transmute::<[u8; CLUSTER_SIZE], Cluster>(bytes) });
+            }
+
+            while window_start < lo {
+                window.pop_front();
+                window_start += 1;
+            }
+
+            *cluster = Cluster::merge(&window.make_contiguous()[lo - window_start..hi - window_start]);
+        }
+
+        self.age.store(header[16] & AGE_MASK, Ordering::Relaxed);
+        Ok(())
+    }
+
     pub fn increment_age(&self) {
         self.age.store((self.age() + 1) & AGE_MASK, Ordering::Relaxed);
     }
@@ -408,3 +522,52 @@ unsafe fn parallel_clear<T: std::marker::Send>(threads: usize, ptr: *mut T, len:
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        random::Random,
+        types::{MoveKind, Square},
+    };
+
+    #[test]
+    fn test_save_load() {
+        let path = std::env::temp_dir().join(format!("reckless-tt-{}.bin", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        let mut rng = Random::new(0x5eed);
+        let hashes = (0..500).map(|_| rng.next_u64()).collect::<Vec<_>>();
+        let mv = Move::new(Square::E2, Square::E4, MoveKind::DoublePush);
+
+        let saved = TranspositionTable::default();
+        saved.resize(1, 4);
+        saved.increment_age();
+
+        for (i, &hash) in hashes.iter().enumerate() {
+            saved.write(hash, 1 + i as i32 % 40, -(i as i32), i as i32, Bound::Lower, mv, 0, false, false);
+        }
+        saved.save(path).unwrap();
+
+        // Same size, shrinking and growing by whole factors, and an uneven ratio
+        for megabytes in [4, 2, 8, 3] {
+            let loaded = TranspositionTable::default();
+            loaded.resize(1, megabytes);
+            loaded.load(path).unwrap();
+
+            assert_eq!(loaded.age(), saved.age());
+
+            for (i, &hash) in hashes.iter().enumerate() {
+                let entry = loaded.read(hash, 0, 0).unwrap_or_else(|| panic!("entry {i} lost at {megabytes} MB"));
+
+                assert_eq!((entry.depth, entry.score, entry.raw_eval), (1 + i as i32 % 40, i as i32, -(i as i32)));
+                assert!(entry.bound == Bound::Lower && entry.mv == mv);
+            }
+        }
+
+        std::fs::write(path, b"not a hash file").unwrap();
+        assert!(TranspositionTable::default().load(path).is_err());
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -6,6 +6,7 @@ pub mod keys;
 pub mod movelist;
 pub mod moves;
 pub mod piece;
+pub mod polyglot;
 pub mod score;
 pub mod square;
 pub mod zobrist;
@@ -18,6 +19,7 @@ pub use keys::*;
 pub use movelist::*;
 pub use moves::*;
 pub use piece::*;
+pub use polyglot::*;
 pub use score::*;
 pub use square::*;
 pub use zobrist::*;
@@ -1,7 +1,7 @@
 use std::mem;
 
 use super::{PieceType, Square};
-use crate::board::Board;
+use crate::board::{Board, NullBoardObserver};
 
 /// Represents a chess move containing the from and to squares, as well as flags for special moves.
 /// The information encoded as a 16-bit integer, 6 bits for the from/to square and 4 bits for the flags.
@@ -53,6 +53,11 @@ impl Move {
         unsafe { mem::transmute((self.0 >> 12) as u8) }
     }
 
+    /// Returns the 16-bit encoding of the move, suitable for storing on disk.
+    pub const fn raw(self) -> u16 {
+        self.0
+    }
+
     pub const fn is_present(self) -> bool {
         !self.is_null()
     }
@@ -158,8 +163,78 @@ impl Move {
 
         output
     }
+
+    /// Formats the move in [Standard Algebraic Notation][san], including check and mate suffixes.
+    /// Castling is written as `O-O` or `O-O-O` in both standard chess and Chess960.
+    ///
+    /// [san]: https://en.wikipedia.org/wiki/Algebraic_notation_(chess)
+    pub fn to_san(self, board: &Board) -> String {
+        let piece_type = board.piece_on(self.from()).piece_type();
+
+        let mut output = if self.is_castling() {
+            if self.to().is_kingside() { "O-O" } else { "O-O-O" }.to_string()
+        } else {
+            let mut output = String::new();
+
+            if piece_type == PieceType::Pawn {
+                if self.is_capture() {
+                    output.push_str(&self.from().to_string()[..1]);
+                }
+            } else {
+                output.push(PIECE_LETTERS[piece_type as usize]);
+
+                let others = board
+                    .generate_all_moves()
+                    .iter()
+                    .map(|entry| entry.mv)
+                    .filter(|&mv| {
+                        mv != self
+                            && !mv.is_castling()
+                            && mv.to() == self.to()
+                            && board.piece_on(mv.from()).piece_type() == piece_type
+                    })
+                    .collect::<Vec<_>>();
+
+                let from = self.from().to_string();
+
+                if others.iter().any(|mv| mv.from().file() == self.from().file()) {
+                    if others.iter().any(|mv| mv.from().rank() == self.from().rank()) {
+                        output.push_str(&from);
+                    } else {
+                        output.push_str(&from[1..]);
+                    }
+                } else if !others.is_empty() {
+                    output.push_str(&from[..1]);
+                }
+            }
+
+            if self.is_capture() {
+                output.push('x');
+            }
+
+            output.push_str(&self.to().to_string());
+
+            if self.is_promotion() {
+                output.push('=');
+                output.push(PIECE_LETTERS[self.promo_piece_type() as usize]);
+            }
+
+            output
+        };
+
+        let mut board = board.clone();
+        board.make_move(self, &mut NullBoardObserver);
+
+        if board.in_check() {
+            output.push(if board.has_legal_moves() { '+' } else { '#' });
+        }
+
+        output
+    }
 }
 
+const PIECE_LETTERS: [char; PieceType::NUM] = ['P', 'N', 'B', 'R', 'Q', 'K'];
+
 impl Default for Move {
     fn default() -> Self {
         Move::NULL
@@ -56,6 +56,42 @@ pub const fn is_valid(score: i32) -> bool {
 }
 
 pub fn normalize_to_cp(score: i32, board: &Board) -> i32 {
+    let (a, _) = win_rate_parameters(board);
+
+    (100.0 * score as f64 / a).round() as i32
+}
+
+/// Returns the expected win, draw and loss probabilities in permille for the side to move.
+///
+/// The win rate follows a logistic model `1 / (1 + exp((a - score) / b))`, where `a` is the
+/// same material-dependent normalization used by `normalize_to_cp`, so that a score of +100 cp
+/// corresponds to a 50% chance of winning.
+///
+/// The coefficients of `a` are the existing centipawn normalization. The coefficients of `b`,
+/// which sets how quickly the win rate rises with the score and thereby the draw rate, are an
+/// unfitted placeholder: they have not been fitted to this engine's game outcomes, so the
+/// reported probabilities are only indicative until `a` and `b` are refitted together, such
+/// as with the [WDL_model] tool.
+///
+/// [WDL_model]: https://github.com/official-stockfish/WDL_model
+pub fn win_rate_model(score: i32, board: &Board) -> (i32, i32, i32) {
+    if is_win(score) {
+        return (1000, 0, 0);
+    }
+
+    if is_loss(score) {
+        return (0, 0, 1000);
+    }
+
+    let (a, b) = win_rate_parameters(board);
+
+    let win = (1000.0 / (1.0 + ((a - score as f64) / b).exp())).round() as i32;
+    let loss = (1000.0 / (1.0 + ((a + score as f64) / b).exp())).round() as i32;
+
+    (win, 1000 - win - loss, loss)
+}
+
+fn win_rate_parameters(board: &Board) -> (f64, f64) {
     let material = board.pieces(PieceType::Pawn).popcount()
         + 3 * board.pieces(PieceType::Knight).popcount()
         + 3 * board.pieces(PieceType::Bishop).popcount()
@@ -64,7 +100,47 @@ pub fn normalize_to_cp(score: i32, board: &Board) -> i32 {
 
     let v = material.clamp(16, 78) as f64 / 58.0;
 
-    let normalization = -285.1 * v.powi(3) + 642.5 * v.powi(2) - 455.5 * v + 464.8;
+    let a = -285.1 * v.powi(3) + 642.5 * v.powi(2) - 455.5 * v + 464.8;
+    // Placeholder, not fitted to this engine's games
+    let b = -3.9 * v.powi(3) + 31.4 * v.powi(2) - 69.4 * v + 89.8;
+
+    (a, b)
+}
 
-    (100.0 * score as f64 / normalization).round() as i32
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_win_rate_model() {
+        let boards = [
+            Board::starting_position(),
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap(),
+            Board::from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1").unwrap(),
+        ];
+
+        for board in &boards {
+            let (a, _) = win_rate_parameters(board);
+            assert!((win_rate_model(a.round() as i32, board).0 - 500).abs() <= 2);
+            assert_eq!(normalize_to_cp(a.round() as i32, board), 100);
+
+            let mut previous = (0, 1000, 1000);
+            for score in (-3000..=3000).step_by(10) {
+                let (win, draw, loss) = win_rate_model(score, board);
+
+                assert_eq!(win + draw + loss, 1000, "score {score}");
+                assert!([win, draw, loss].iter().all(|p| (0..=1000).contains(p)), "score {score}");
+                assert!(win >= previous.0 && loss <= previous.2, "score {score}");
+
+                previous = (win, draw, loss);
+            }
+
+            let (win, draw, loss) = win_rate_model(0, board);
+            assert_eq!(win, loss);
+            assert!(draw > 0);
+
+            assert_eq!(win_rate_model(mate_in(3), board), (1000, 0, 0));
+            assert_eq!(win_rate_model(mated_in(3), board), (0, 0, 1000));
+        }
+    }
 }
@@ -1,18 +1,27 @@
 use std::collections::HashMap;
 use std::collections::VecDeque;
-use std::sync::Arc;
+use std::sync::{Arc, atomic::Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::{
-    board::{Board, NullBoardObserver},
+    board::{Board, BoardEditor, NullBoardObserver},
+    book::Book,
+    experience::{DEFAULT_EXPERIENCE_FILE, Experience},
+    nnue, pgn,
+    random::Random,
     search::Report,
-    thread::{SharedContext, Status, ThreadData},
+    skill::{self, Skill},
+    thread::{DEFAULT_CURRMOVE_THRESHOLD, RootMove, SharedContext, Status, ThreadData, uci_score},
     threadpool::ThreadPool,
-    time::{Limits, TimeManager},
+    time::{Clock, Limits, TimeManager},
     tools,
     transposition::DEFAULT_TT_SIZE,
     types::{Color, MAX_MOVES, Move, Piece, Score, Square, is_decisive, is_loss, is_win},
 };
 
+const DEFAULT_HASH_FILE: &str = "hash.bin";
+const DEFAULT_BOOK_DEPTH: usize = 20;
+
 #[derive(Copy, Clone, PartialEq, Eq)]
 enum Mode {
     Cli,
@@ -21,27 +30,68 @@ enum Mode {
 
 struct Settings {
     frc: bool,
+    ponder: bool,
     multi_pv: usize,
     move_overhead: u64,
     report: Report,
+    skill_level: i32,
+    skill_seed: u64,
+    hash_file: String,
+    experience_file: String,
+    experience: Option<Experience>,
+    own_book: bool,
+    book_depth: usize,
+    book_best_move: bool,
+    book: Option<Book>,
+    #[cfg(feature = "trace")]
+    trace_file: Option<String>,
+    #[cfg(feature = "trace")]
+    trace_nodes: usize,
+    #[cfg(feature = "trace")]
+    trace_ply: isize,
+}
+
+impl Settings {
+    fn skill(&self) -> Skill {
+        Skill::new(self.skill_level as f64)
+    }
 }
 
 impl Default for Settings {
     fn default() -> Self {
         Self {
             frc: false,
+            ponder: false,
             multi_pv: 1,
             move_overhead: 100,
             report: Report::Full,
+            skill_level: skill::MAX_LEVEL as i32,
+            skill_seed: 0,
+            hash_file: DEFAULT_HASH_FILE.to_string(),
+            experience_file: DEFAULT_EXPERIENCE_FILE.to_string(),
+            experience: None,
+            own_book: false,
+            book_depth: DEFAULT_BOOK_DEPTH,
+            book_best_move: false,
+            book: None,
+            #[cfg(feature = "trace")]
+            trace_file: None,
+            #[cfg(feature = "trace")]
+            trace_nodes: crate::trace::DEFAULT_TRACE_NODES,
+            #[cfg(feature = "trace")]
+            trace_ply: crate::trace::DEFAULT_TRACE_PLY,
         }
     }
 }
 
-pub fn message_loop(mut buffer: VecDeque<String>) {
+pub fn message_loop(mut buffer: VecDeque<String>, json_output: bool) {
     let shared = Arc::new(SharedContext::default());
+    shared.json_output.store(json_output, Ordering::Relaxed);
+
     let mut settings = Settings::default();
     let mut threads = ThreadPool::new(shared.clone());
     let mut board = Board::starting_position();
+    let mut game = None;
 
     let rx = spawn_listener(shared.clone());
 
@@ -67,12 +117,20 @@ pub fn message_loop(mut buffer: VecDeque<String>) {
             }
 
             ["isready"] => println!("readyok"),
+            ["debug", "on"] => {
+                shared.debug.store(true, Ordering::Relaxed);
+
+                #[cfg(not(feature = "stats"))]
+                println!("info string debug diagnostics require the stats feature");
+            }
+            ["debug", "off"] => shared.debug.store(false, Ordering::Relaxed),
 
-            ["go", tokens @ ..] => go(&mut threads, &settings, &board, &shared, tokens),
+            ["go", tokens @ ..] => go(&mut threads, &mut settings, &board, &shared, tokens),
             ["position", tokens @ ..] => position(&mut board, &settings, tokens),
             ["setoption", tokens @ ..] => set_option(&mut threads, &mut settings, &shared, tokens),
             ["ucinewgame"] => reset(&mut threads, &shared),
 
+            ["ponderhit"] => shared.pondering.set(false),
             ["stop"] => shared.status.set(Status::STOPPED),
             ["quit"] => {
                 drop(threads);
@@ -83,13 +141,30 @@ pub fn message_loop(mut buffer: VecDeque<String>) {
             ["compiler"] => compiler(),
             ["eval"] => eval(threads.main_thread(), &board),
             ["d"] => println!("{board}"),
-            ["bench", args @ ..] => match mode {
-                Mode::Uci => tools::bench::<true>(args),
-                Mode::Cli => tools::bench::<false>(args),
-            },
-            ["speedtest", args @ ..] => tools::speedtest(args),
+            ["book"] => book(&settings, &board),
+            ["pgn", tokens @ ..] => pgn(&mut board, &mut game, &settings, tokens),
+            ["setup", tokens @ ..] => setup(&mut board, &settings, tokens),
+            ["netinfo"] => netinfo(),
+            ["netheader", input, output] => netheader(input, output),
+            ["bench", args @ ..] => {
+                match mode {
+                    Mode::Uci => tools::bench::<true>(args),
+                    Mode::Cli => tools::bench::<false>(args),
+                }
+                dbg_print(&shared);
+            }
+            ["speedtest", args @ ..] => {
+                tools::speedtest(args);
+                dbg_print(&shared);
+            }
+            ["datagen", args @ ..] => tools::datagen(args),
+            ["traceview", args @ ..] => tools::traceview(args),
+            ["convert", args @ ..] => tools::convert(args),
+            ["epd", args @ ..] => tools::epd(&mut threads, &shared, args),
             ["perft", depth] => tools::perft(depth.parse().unwrap(), &mut board),
             ["perft"] => eprintln!("Usage: perft <depth>"),
+            ["parallelperft", args @ ..] => tools::parallel_perft(&threads, &board, args),
+            ["perftsuite", args @ ..] => tools::perft_suite(&threads, args),
             ["simpleperft", depth] => tools::simple_perft(depth.parse().unwrap(), &mut board),
             ["simpleperft"] => eprintln!("Usage: simpleperft <depth>"),
             ["islegalperft", depth] => tools::is_legal_perft(depth.parse().unwrap(), &mut board),
@@ -125,8 +200,13 @@ fn spawn_listener(shared: Arc<SharedContext>) -> std::sync::mpsc::Receiver<Strin
 
             match message.trim_end() {
                 "isready" => println!("readyok"),
-                "stop" => shared.status.set(Status::STOPPED),
+                "ponderhit" => shared.pondering.set(false),
+                "stop" => {
+                    shared.pondering.set(false);
+                    shared.status.set(Status::STOPPED);
+                }
                 "quit" => {
+                    shared.pondering.set(false);
                     shared.status.set(Status::STOPPED);
                     let _ = tx.send("quit".to_string());
                     break;
@@ -154,12 +234,43 @@ fn uci() {
     println!("option name MoveOverhead type spin default 100 min 0 max 2000");
     println!("option name Minimal type check default false");
     println!("option name Clear Hash type button");
+    println!("option name HashFile type string default {DEFAULT_HASH_FILE}");
+    println!("option name Hash Save type button");
+    println!("option name Hash Load type button");
+    println!("option name OwnBook type check default false");
+    println!("option name BookFile type string default <empty>");
+    println!("option name BookDepth type spin default {DEFAULT_BOOK_DEPTH} min 1 max 1000");
+    println!("option name BookBestMove type check default false");
+    println!("option name Experience type check default false");
+    println!("option name ExperienceFile type string default {DEFAULT_EXPERIENCE_FILE}");
     println!("option name UCI_Chess960 type check default false");
     println!("option name MultiPV type spin default 1 min 1 max {MAX_MOVES}");
+    println!("option name Ponder type check default false");
+    println!("option name UCI_ShowWDL type check default false");
+    println!("option name JsonOutput type check default false");
+    println!("option name CurrMoveThreshold type spin default {DEFAULT_CURRMOVE_THRESHOLD} min 0 max 3600000");
+    println!("option name EvalFile type string default <empty>");
+    println!("option name Skill Level type spin default {} min 0 max {}", skill::MAX_LEVEL, skill::MAX_LEVEL);
+    println!("option name SkillSeed type spin default 0 min 0 max {}", i64::MAX);
 
     #[cfg(feature = "syzygy")]
     println!("option name SyzygyPath type string default");
 
+    #[cfg(feature = "trace")]
+    {
+        println!("option name TraceFile type string default <empty>");
+        println!(
+            "option name TraceNodes type spin default {} min 1 max {}",
+            crate::trace::DEFAULT_TRACE_NODES,
+            u32::MAX
+        );
+        println!(
+            "option name TracePly type spin default {} min 0 max {}",
+            crate::trace::DEFAULT_TRACE_PLY,
+            crate::types::MAX_PLY
+        );
+    }
+
     #[cfg(feature = "spsa")]
     crate::parameters::print_options();
 
@@ -183,11 +294,46 @@ fn reset(threads: &mut ThreadPool, shared: &Arc<SharedContext>) {
     }
 }
 
-fn go(threads: &mut ThreadPool, settings: &Settings, board: &Board, shared: &Arc<SharedContext>, tokens: &[&str]) {
-    let limits = parse_limits(board.side_to_move(), tokens);
+fn go(threads: &mut ThreadPool, settings: &mut Settings, board: &Board, shared: &Arc<SharedContext>, tokens: &[&str]) {
+    let ponder = tokens.contains(&"ponder");
+    let debug = tokens.contains(&"debug");
+    let tokens = tokens.iter().copied().filter(|&token| token != "ponder" && token != "debug").collect::<Vec<_>>();
+    let (search_moves, tokens) = parse_search_moves(board, &tokens);
+
+    if !ponder
+        && search_moves.is_none()
+        && !tokens.contains(&"infinite")
+        && let Some(mv) = book_move(settings, board)
+    {
+        println!("bestmove {}", mv.to_uci(board));
+        return;
+    }
+
+    let skill = settings.skill();
+    let mut limits = parse_limits(board.side_to_move(), &tokens);
+    let mut multi_pv = settings.multi_pv;
+
+    if skill.enabled() {
+        limits.nodes = Some(limits.nodes.map_or(skill.node_limit(), |nodes| nodes.min(skill.node_limit())));
+        multi_pv = multi_pv.max(skill::MULTI_PV);
+    }
+
     let time_manager = TimeManager::new(limits, board.fullmove_number(), settings.move_overhead);
 
-    threads.execute_searches(time_manager, settings.report, settings.multi_pv, board, shared);
+    shared.pondering.set(ponder);
+
+    if let Some(experience) = &settings.experience {
+        experience.inject(board, &shared.tt);
+    }
+
+    #[cfg(feature = "trace")]
+    threads.main_thread().trace.configure(settings.trace_file.clone(), settings.trace_nodes, settings.trace_ply);
+
+    threads.execute_searches(time_manager, settings.report, multi_pv, search_moves.as_deref(), board, shared);
+
+    // The UCI protocol forbids sending `bestmove` while pondering,
+    // even if the search has already finished on its own.
+    shared.pondering.wait();
 
     if threads[0].root_moves.is_empty() {
         println!("bestmove (none)");
@@ -204,7 +350,7 @@ fn go(threads: &mut ThreadPool, settings: &Settings, board: &Board, shared: &Arc
 
     let mut best = 0;
 
-    if !matches!(threads[best].time_manager.limits(), Limits::Depth(_)) && threads[0].multi_pv == 1 {
+    if threads[best].time_manager.limits().depth.is_none() && threads[0].multi_pv == 1 {
         for current in 1..threads.len() {
             let is_better_candidate = || -> bool {
                 let best = &threads[best];
@@ -243,8 +389,108 @@ fn go(threads: &mut ThreadPool, settings: &Settings, board: &Board, shared: &Arc
         threads[best].print_uci_info(threads[best].completed_depth);
     }
 
-    println!("bestmove {}", threads[best].root_moves[0].mv.to_uci(board));
-    crate::misc::dbg_print();
+    let mut index = 0;
+
+    if skill.enabled() {
+        let mut rng = Random::new(settings.skill_seed ^ board.hash());
+        index = skill.pick_move(&threads[best].root_moves, threads[best].multi_pv, &mut rng);
+    }
+
+    let root_move = &threads[best].root_moves[index];
+    let best_move = root_move.mv;
+
+    match ponder_move(root_move, board, shared) {
+        Some(ponder) if settings.ponder => {
+            let mut next = board.clone();
+            next.make_move(best_move, &mut NullBoardObserver);
+            println!("bestmove {} ponder {}", best_move.to_uci(board), ponder.to_uci(&next));
+        }
+        _ => println!("bestmove {}", best_move.to_uci(board)),
+    }
+
+    // Restricted searches do not reflect the true value of the position
+    let best_result = &threads[best].root_moves[0];
+    if let Some(experience) = &mut settings.experience
+        && search_moves.is_none()
+        && !best_result.upperbound
+        && !best_result.lowerbound
+        && let Err(error) = experience.record(board, best_result.mv, best_result.score, threads[best].completed_depth)
+    {
+        eprintln!("Failed to write experience file '{}': {error}", settings.experience_file);
+    }
+
+    if debug {
+        print_root_moves(threads, best, board);
+    }
+
+    dbg_print(shared);
+}
+
+/// Prints the `misc::dbg_*` slots wired into the code by hand, once enabled with `debug on`.
+fn dbg_print(shared: &SharedContext) {
+    if shared.debug.load(Ordering::Relaxed) {
+        crate::misc::dbg_print();
+    }
+}
+
+/// Prints the nodes spent on every root move across all threads,
+/// together with the score and bound found by the best thread.
+fn print_root_moves(threads: &ThreadPool, best: usize, board: &Board) {
+    let total = threads.iter().flat_map(|td| &td.root_moves).map(|rm| rm.nodes).sum::<u64>().max(1);
+
+    let mut root_moves = threads[best].root_moves.iter().collect::<Vec<_>>();
+    let nodes_of = |mv: Move| -> u64 {
+        threads.iter().flat_map(|td| &td.root_moves).filter(|rm| rm.mv == mv).map(|rm| rm.nodes).sum()
+    };
+    root_moves.sort_by_key(|rm| std::cmp::Reverse(nodes_of(rm.mv)));
+
+    for root_move in root_moves {
+        // Moves that failed low without ever being resolved have no score to report
+        let score = match [root_move.score, root_move.previous_score].into_iter().find(|&v| v != -Score::INFINITE) {
+            Some(score) => {
+                let (kind, value) = uci_score(score, board);
+                format!("{kind} {value}")
+            }
+            None => "-".to_string(),
+        };
+
+        let bound = match (root_move.score != -Score::INFINITE, root_move.upperbound, root_move.lowerbound) {
+            (false, ..) | (true, true, _) => "upperbound",
+            (true, false, true) => "lowerbound",
+            (true, false, false) => "exact",
+        };
+
+        let nodes = nodes_of(root_move.mv);
+        println!(
+            "info string move {:<6} nodes {nodes:>12} ({:5.1}%) score {score} {bound}",
+            root_move.mv.to_uci(board),
+            100.0 * nodes as f64 / total as f64
+        );
+    }
+}
+
+fn book_move(settings: &Settings, board: &Board) -> Option<Move> {
+    let book = settings.book.as_ref().filter(|_| settings.own_book)?;
+    if board.fullmove_number() > settings.book_depth {
+        return None;
+    }
+
+    let seed = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_nanos() as u64);
+    book.pick(board, settings.book_best_move, &mut Random::new(seed))
+}
+
+/// Returns the expected reply to the best move, taken from the principal variation
+/// or, if the search did not get that far, from the transposition table.
+fn ponder_move(root_move: &RootMove, board: &Board, shared: &SharedContext) -> Option<Move> {
+    if let Some(&mv) = root_move.pv.line().first() {
+        return Some(mv);
+    }
+
+    let mut board = board.clone();
+    board.make_move(root_move.mv, &mut NullBoardObserver);
+
+    let entry = shared.tt.read(board.hash(), board.fiftymove_clock(), 0)?;
+    (entry.mv.is_present() && board.is_legal(entry.mv)).then_some(entry.mv)
 }
 
 fn position(board: &mut Board, settings: &Settings, mut tokens: &[&str]) {
@@ -255,11 +501,29 @@ fn position(board: &mut Board, settings: &Settings, mut tokens: &[&str]) {
                 tokens = rest;
             }
             ["fen", rest @ ..] => {
-                match Board::from_fen(&rest.join(" ")) {
+                let length = rest.iter().position(|&token| token == "moves").unwrap_or(rest.len());
+                let fen = rest[..length].join(" ");
+
+                match Board::from_fen_strict(&fen) {
                     Ok(b) => *board = b,
-                    Err(e) => eprintln!("Invalid FEN: {e:?}"),
+                    Err(error) => {
+                        eprintln!("Invalid FEN '{fen}': {error}");
+                        return;
+                    }
                 }
                 board.set_frc(settings.frc);
+                tokens = &rest[length..];
+            }
+            ["pgn", path, rest @ ..] => {
+                let (ply, rest) = match rest {
+                    [ply, rest @ ..] if ply.parse::<usize>().is_ok() => (ply.parse().ok(), rest),
+                    _ => (None, rest),
+                };
+
+                match load_pgn(path, 1, settings.frc) {
+                    Some(game) => *board = game.board_at(ply.unwrap_or(game.moves.len())),
+                    None => return,
+                }
                 tokens = rest;
             }
             ["moves", rest @ ..] => {
@@ -273,13 +537,144 @@ fn position(board: &mut Board, settings: &Settings, mut tokens: &[&str]) {
     }
 }
 
+/// Handles the `pgn` command, which loads a game and navigates through its plies.
+fn pgn(board: &mut Board, game: &mut Option<pgn::Game>, settings: &Settings, tokens: &[&str]) {
+    match tokens {
+        ["load", path, rest @ ..] => {
+            let index = rest.first().and_then(|index| index.parse().ok()).unwrap_or(1);
+
+            if let Some(loaded) = load_pgn(path, index, settings.frc) {
+                *board = loaded.board_at(loaded.moves.len());
+                print_game(&loaded);
+                *game = Some(loaded);
+            }
+        }
+        ["ply", ply] => match (game.as_ref(), ply.parse::<usize>()) {
+            (Some(game), Ok(ply)) => {
+                *board = game.board_at(ply);
+                println!("{board}");
+            }
+            (None, _) => eprintln!("No game loaded"),
+            (_, Err(_)) => eprintln!("Invalid ply: '{ply}'"),
+        },
+        [] => match game.as_ref() {
+            Some(game) => print_game(game),
+            None => eprintln!("No game loaded"),
+        },
+        _ => eprintln!("Usage: pgn [load <file> [game] | ply <n>]"),
+    }
+}
+
+/// Handles the `setup` command, which edits the current position and replaces it
+/// if the result is legal, e.g. `setup clear put Ke1 ke8 Qd1 side b castling -`.
+fn setup(board: &mut Board, settings: &Settings, mut tokens: &[&str]) {
+    const KEYWORDS: [&str; 8] = ["clear", "put", "remove", "side", "castling", "ep", "halfmove", "fullmove"];
+
+    let mut editor = board.edit();
+    editor.set_frc(settings.frc);
+
+    while let [keyword, rest @ ..] = tokens {
+        let count = rest.iter().take_while(|token| !KEYWORDS.contains(token)).count();
+        let (args, rest) = rest.split_at(count);
+
+        if apply_setup(&mut editor, keyword, args).is_none() {
+            eprintln!("Invalid setup argument '{}'", [&[*keyword], args].concat().join(" "));
+            return;
+        }
+        tokens = rest;
+    }
+
+    match editor.finish() {
+        Ok(edited) => *board = edited,
+        Err(error) => eprintln!("Failed to set up position '{}': {error}", editor.to_fen()),
+    }
+}
+
+fn apply_setup(editor: &mut BoardEditor, keyword: &str, args: &[&str]) -> Option<()> {
+    match (keyword, args) {
+        ("clear", []) => editor.clear(),
+        ("put", [_, ..]) => {
+            for arg in args {
+                let piece = arg.chars().next()?.try_into().ok()?;
+                editor.place(piece, arg.get(1..)?.try_into().ok()?);
+            }
+        }
+        ("remove", [_, ..]) => {
+            for &arg in args {
+                editor.remove(arg.try_into().ok()?);
+            }
+        }
+        ("side", ["w"]) => editor.set_side_to_move(Color::White),
+        ("side", ["b"]) => editor.set_side_to_move(Color::Black),
+        ("castling", [rights]) => editor.set_castling(rights),
+        ("ep", ["-"]) => editor.set_en_passant(Square::None),
+        ("ep", [square]) => editor.set_en_passant((*square).try_into().ok()?),
+        ("halfmove", [clock]) => editor.set_fiftymove_clock(clock.parse().ok()?),
+        ("fullmove", [number]) => editor.set_fullmove_number(number.parse().ok()?),
+        _ => return None,
+    }
+
+    Some(())
+}
+
+/// Reads the `index`-th game (counting from 1) of a PGN file.
+fn load_pgn(path: &str, index: usize, frc: bool) -> Option<pgn::Game> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(error) => {
+            eprintln!("Failed to read '{path}': {error}");
+            return None;
+        }
+    };
+
+    match pgn::parse(&text, frc) {
+        Ok(mut games) if (1..=games.len()).contains(&index) => Some(games.swap_remove(index - 1)),
+        Ok(games) => {
+            eprintln!("Game {index} not found, '{path}' contains {} games", games.len());
+            None
+        }
+        Err(error) => {
+            eprintln!("Failed to parse '{path}': {error}");
+            None
+        }
+    }
+}
+
+fn print_game(game: &pgn::Game) {
+    for (name, value) in &game.tags {
+        println!("[{name} \"{}\"]", value.replace('\\', "\\\\").replace('"', "\\\""));
+    }
+
+    let mut board = game.start.clone();
+
+    for (ply, &mv) in game.moves.iter().enumerate() {
+        let white = board.side_to_move() == Color::White;
+
+        if white || ply == 0 {
+            if ply > 0 {
+                println!();
+            }
+            print!("{:>4}{:<4}", board.fullmove_number(), if white { "." } else { "..." });
+        }
+
+        print!("{:<12}", format!("{}:{}", ply + 1, mv.to_san(&board)));
+        board.make_move(mv, &mut NullBoardObserver);
+    }
+
+    println!("\n{}", game.result);
+}
+
 fn make_uci_move(board: &mut Board, uci_move: &str) {
-    let moves = board.generate_all_moves();
-    if let Some(mv) = moves.iter().map(|entry| entry.mv).find(|mv| mv.to_uci(board) == uci_move) {
+    if let Some(mv) = find_uci_move(board, uci_move) {
         board.make_move(mv, &mut NullBoardObserver);
     }
 }
 
+fn find_uci_move(board: &Board, uci_move: &str) -> Option<Move> {
+    let moves = board.generate_all_moves();
+    moves.iter().map(|entry| entry.mv).find(|mv| mv.to_uci(board) == uci_move)
+}
+
 fn set_option(threads: &mut ThreadPool, settings: &mut Settings, shared: &Arc<SharedContext>, tokens: &[&str]) {
     match tokens {
         ["name", "Minimal", "value", v] => match *v {
@@ -291,6 +686,60 @@ fn set_option(threads: &mut ThreadPool, settings: &mut Settings, shared: &Arc<Sh
             shared.tt.clear(threads.len());
             println!("info string Hash cleared");
         }
+        ["name", "HashFile", "value", path @ ..] => {
+            settings.hash_file = path.join(" ");
+            println!("info string set HashFile to {}", settings.hash_file);
+        }
+        ["name", "Hash", "Save"] => match shared.tt.save(&settings.hash_file) {
+            Ok(()) => println!("info string Hash saved to {}", settings.hash_file),
+            Err(error) => eprintln!("Failed to save hash to '{}': {error}", settings.hash_file),
+        },
+        ["name", "Hash", "Load"] => match shared.tt.load(&settings.hash_file) {
+            Ok(()) => println!("info string Hash loaded from {}", settings.hash_file),
+            Err(error) => eprintln!("Failed to load hash from '{}': {error}", settings.hash_file),
+        },
+        ["name", "OwnBook", "value", v] => {
+            settings.own_book = v.parse().unwrap_or_default();
+            println!("info string set OwnBook to {v}");
+        }
+        ["name", "BookFile", "value", path @ ..] => {
+            let path = path.join(" ");
+            match path.as_str() {
+                "" | "<empty>" => {
+                    settings.book = None;
+                    println!("info string set BookFile to <empty>");
+                }
+                _ => match Book::open(&path) {
+                    Ok(book) => {
+                        println!("info string Loaded {} book entries from {path}", book.len());
+                        settings.book = Some(book);
+                    }
+                    Err(error) => eprintln!("Failed to load book '{path}': {error}"),
+                },
+            }
+        }
+        ["name", "BookDepth", "value", v] => {
+            settings.book_depth = v.parse().unwrap_or(DEFAULT_BOOK_DEPTH);
+            println!("info string set BookDepth to {v}");
+        }
+        ["name", "BookBestMove", "value", v] => {
+            settings.book_best_move = v.parse().unwrap_or_default();
+            println!("info string set BookBestMove to {v}");
+        }
+        ["name", "Experience", "value", v] => {
+            settings.experience = None;
+            if v.parse().unwrap_or_default() {
+                open_experience(settings);
+            }
+            println!("info string set Experience to {v}");
+        }
+        ["name", "ExperienceFile", "value", path @ ..] => {
+            settings.experience_file = path.join(" ");
+            if settings.experience.is_some() {
+                open_experience(settings);
+            }
+            println!("info string set ExperienceFile to {}", settings.experience_file);
+        }
         ["name", "Hash", "value", v] => {
             shared.tt.resize(threads.len(), v.parse().unwrap());
             println!("info string set Hash to {v} MB");
@@ -308,6 +757,25 @@ fn set_option(threads: &mut ThreadPool, settings: &mut Settings, shared: &Arc<Sh
             Some(size) => println!("info string Loaded Syzygy tablebases with {size} pieces"),
             None => eprintln!("Failed to load Syzygy tablebases"),
         },
+        #[cfg(feature = "trace")]
+        ["name", "TraceFile", "value", path @ ..] => {
+            let path = path.join(" ");
+            settings.trace_file = match path.as_str() {
+                "" | "<empty>" => None,
+                _ => Some(path),
+            };
+            println!("info string set TraceFile to {}", settings.trace_file.as_deref().unwrap_or("<empty>"));
+        }
+        #[cfg(feature = "trace")]
+        ["name", "TraceNodes", "value", v] => {
+            settings.trace_nodes = v.parse().unwrap_or(crate::trace::DEFAULT_TRACE_NODES);
+            println!("info string set TraceNodes to {v}");
+        }
+        #[cfg(feature = "trace")]
+        ["name", "TracePly", "value", v] => {
+            settings.trace_ply = v.parse().unwrap_or(crate::trace::DEFAULT_TRACE_PLY);
+            println!("info string set TracePly to {v}");
+        }
         ["name", "UCI_Chess960", "value", v] => {
             settings.frc = v.parse().unwrap_or_default();
             println!("info string set UCI_Chess960 to {v}");
@@ -316,6 +784,46 @@ fn set_option(threads: &mut ThreadPool, settings: &mut Settings, shared: &Arc<Sh
             settings.multi_pv = v.parse().unwrap_or_default();
             println!("info string set MultiPV to {v}");
         }
+        ["name", "UCI_ShowWDL", "value", v] => {
+            shared.show_wdl.store(v.parse().unwrap_or_default(), Ordering::Relaxed);
+            println!("info string set UCI_ShowWDL to {v}");
+        }
+        ["name", "CurrMoveThreshold", "value", v] => {
+            shared.currmove_threshold.store(v.parse().unwrap_or(DEFAULT_CURRMOVE_THRESHOLD), Ordering::Relaxed);
+            println!("info string set CurrMoveThreshold to {v} ms");
+        }
+        ["name", "JsonOutput", "value", v] => {
+            shared.json_output.store(v.parse().unwrap_or_default(), Ordering::Relaxed);
+            println!("info string set JsonOutput to {v}");
+        }
+        ["name", "EvalFile", "value", path @ ..] => {
+            let path = path.join(" ");
+            let source = match path.as_str() {
+                "" | "<empty>" => None,
+                path => Some(path),
+            };
+
+            match nnue::load(source) {
+                Ok(()) => {
+                    shared.parameters.reload();
+                    threads.clear();
+                    println!("info string set EvalFile to {}", source.unwrap_or("<empty>"));
+                }
+                Err(error) => eprintln!("Failed to load network '{path}': {error}"),
+            }
+        }
+        ["name", "Skill", "Level", "value", v] => {
+            settings.skill_level = v.parse().unwrap_or(skill::MAX_LEVEL as i32).clamp(0, skill::MAX_LEVEL as i32);
+            println!("info string set Skill Level to {}", settings.skill_level);
+        }
+        ["name", "SkillSeed", "value", v] => {
+            settings.skill_seed = v.parse().unwrap_or_default();
+            println!("info string set SkillSeed to {v}");
+        }
+        ["name", "Ponder", "value", v] => {
+            settings.ponder = v.parse().unwrap_or_default();
+            println!("info string set Ponder to {v}");
+        }
         #[cfg(feature = "spsa")]
         ["name", name, "value", v] => {
             crate::parameters::set_parameter(name, v);
@@ -325,6 +833,60 @@ fn set_option(threads: &mut ThreadPool, settings: &mut Settings, shared: &Arc<Sh
     }
 }
 
+fn book(settings: &Settings, board: &Board) {
+    let Some(book) = &settings.book else {
+        eprintln!("No book loaded, set BookFile first");
+        return;
+    };
+
+    let moves = book.moves(board);
+    if moves.is_empty() {
+        println!("No book moves for this position");
+        return;
+    }
+
+    let total = moves.iter().map(|book| book.weight as u64).sum::<u64>().max(1);
+    for book in moves {
+        println!(
+            "{:<8} {:<6} weight {:>5} ({:5.1}%)",
+            book.mv.to_san(board),
+            book.mv.to_uci(board),
+            book.weight,
+            100.0 * book.weight as f64 / total as f64
+        );
+    }
+}
+
+fn open_experience(settings: &mut Settings) {
+    settings.experience = match Experience::open(&settings.experience_file) {
+        Ok(experience) => {
+            println!("info string Loaded {} positions from {}", experience.len(), settings.experience_file);
+            Some(experience)
+        }
+        Err(error) => {
+            eprintln!("Failed to open experience file '{}': {error}", settings.experience_file);
+            None
+        }
+    };
+}
+
+fn netinfo() {
+    let (header, path) = nnue::info();
+
+    println!("Source           {}", path.as_deref().unwrap_or("embedded"));
+    println!("{header}");
+}
+
+fn netheader(input: &str, output: &str) {
+    match nnue::add_header(input, output) {
+        Ok(header) => {
+            println!("Wrote {output}");
+            println!("{header}");
+        }
+        Err(error) => eprintln!("Failed to add a header to '{input}': {error}"),
+    }
+}
+
 fn eval(td: &mut ThreadData, board: &Board) {
     td.nnue.full_refresh(board);
     td.nnue.evaluate(board);
@@ -383,52 +945,78 @@ fn eval(td: &mut ThreadData, board: &Board) {
     println!("\nNNUE evaluation        {final_total:+.2} (White side)");
 }
 
-fn parse_limits(color: Color, tokens: &[&str]) -> Limits {
-    if let ["infinite"] = tokens {
-        return Limits::Infinite;
+/// Extracts the moves following `searchmoves`, returning them together with the remaining tokens.
+///
+/// Illegal or malformed moves are skipped. `None` means that the root move list is not restricted,
+/// which is also the case when none of the listed moves is legal.
+fn parse_search_moves<'a>(board: &Board, tokens: &[&'a str]) -> (Option<Vec<Move>>, Vec<&'a str>) {
+    const KEYWORDS: [&str; 11] =
+        ["wtime", "btime", "winc", "binc", "movestogo", "depth", "nodes", "mate", "movetime", "infinite", "ponder"];
+
+    let Some(start) = tokens.iter().position(|&token| token == "searchmoves") else {
+        return (None, tokens.to_vec());
+    };
+
+    let end =
+        tokens[start + 1..].iter().position(|token| KEYWORDS.contains(token)).map_or(tokens.len(), |v| start + 1 + v);
+
+    let moves: Vec<_> = tokens[start + 1..end].iter().filter_map(|uci_move| find_uci_move(board, uci_move)).collect();
+    let rest = tokens[..start].iter().chain(&tokens[end..]).copied().collect();
+
+    ((!moves.is_empty()).then_some(moves), rest)
+}
+
+pub fn parse_limits(color: Color, tokens: &[&str]) -> Limits {
+    let mut limits = Limits::default();
+
+    if tokens.contains(&"infinite") {
+        return limits;
     }
 
     let mut main = None;
     let mut inc = None;
     let mut moves = None;
 
-    for chunk in tokens.chunks(2) {
-        if let [name, value] = *chunk {
-            let Ok(value) = value.parse::<u64>() else {
-                continue;
-            };
+    for pair in tokens.windows(2) {
+        let (name, value) = (pair[0], pair[1]);
 
-            match name {
-                "depth" if value > 0 => return Limits::Depth(value as i32),
-                "movetime" if value > 0 => return Limits::Time(value),
-                "nodes" if value > 0 => return Limits::Nodes(value),
+        let Ok(value) = value.parse::<u64>() else {
+            continue;
+        };
 
-                "wtime" if Color::White == color => main = Some(value),
-                "btime" if Color::Black == color => main = Some(value),
-                "winc" if Color::White == color => inc = Some(value),
-                "binc" if Color::Black == color => inc = Some(value),
-                "movestogo" => moves = Some(value),
+        match name {
+            "depth" if value > 0 => limits.depth = Some(value as i32),
+            "movetime" if value > 0 => limits.movetime = Some(value),
+            "nodes" if value > 0 => limits.nodes = Some(value),
+            "mate" if value > 0 => limits.mate = Some(value as i32),
 
-                _ => continue,
-            }
-        }
-    }
+            "wtime" if Color::White == color => main = Some(value),
+            "btime" if Color::Black == color => main = Some(value),
+            "winc" if Color::White == color => inc = Some(value),
+            "binc" if Color::Black == color => inc = Some(value),
+            "movestogo" if value > 0 => moves = Some(value),
 
-    if main.is_none() && inc.is_none() {
-        return Limits::Infinite;
+            _ => continue,
+        }
     }
 
-    let main = main.unwrap_or_default();
-    let inc = inc.unwrap_or_default();
+    if main.is_some() || inc.is_some() {
+        let main = main.unwrap_or_default();
+        let inc = inc.unwrap_or_default();
 
-    match moves {
-        Some(moves) => Limits::Cyclic(main, inc, moves),
-        None => Limits::Fischer(main, inc),
+        limits.clock = match moves {
+            Some(moves) => Some(Clock::Cyclic(main, inc, moves)),
+            None => Some(Clock::Fischer(main, inc)),
+        };
     }
+
+    limits
 }
 
 #[cfg(test)]
 mod tests {
+    use std::sync::atomic::AtomicBool;
+
     use super::*;
 
     fn test_position_helper(tokens: &[&str]) -> Board {
@@ -536,6 +1124,85 @@ mod tests {
         assert_eq!(board.to_fen(), fen_before);
     }
 
+    #[test]
+    fn test_ponderhit() {
+        let shared = Arc::new(SharedContext::default());
+        let mut threads = ThreadPool::new(shared.clone());
+        let mut settings = Settings { ponder: true, ..Settings::default() };
+        let board = Board::starting_position();
+        let hit = AtomicBool::new(false);
+
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                // Let the search finish on its own, so that only `ponderhit` can release `go`
+                while shared.nodes.aggregate() == 0 || shared.status.get() == Status::RUNNING {
+                    std::thread::sleep(std::time::Duration::from_millis(1));
+                }
+                hit.store(true, Ordering::Relaxed);
+                shared.pondering.set(false);
+            });
+
+            go(&mut threads, &mut settings, &board, &shared, &["ponder", "depth", "4"]);
+            assert!(hit.load(Ordering::Relaxed));
+        });
+
+        let root_move = &threads[0].root_moves[0];
+        assert!(root_move.mv.is_present());
+        assert!(ponder_move(root_move, &board, &shared).is_some());
+    }
+
+    #[test]
+    fn test_go_mate() {
+        let shared = Arc::new(SharedContext::default());
+        let mut threads = ThreadPool::new(shared.clone());
+
+        // 1. Nf6+ gxf6 2. Bxf7#
+        let board = Board::from_fen("r2qkb1r/pp2nppp/3p4/2pNN1B1/2BnP3/3P4/PPP2PPP/R2bK2R w KQkq - 1 1").unwrap();
+
+        // The node limit only guards against the search not stopping on its own
+        let limits = parse_limits(board.side_to_move(), &["mate", "2", "nodes", "50000000"]);
+        threads.execute_searches(TimeManager::new(limits, 0, 0), Report::None, 1, None, &board, &shared);
+
+        let td = threads.main_thread();
+        assert_eq!(td.root_moves[0].mv.to_uci(&board), "d5f6");
+        assert_eq!(td.root_moves[0].score, crate::types::mate_in(3));
+        assert!(shared.nodes.aggregate() < 50_000_000);
+    }
+
+    #[test]
+    fn test_parse_search_moves() {
+        let board = Board::starting_position();
+        let (moves, rest) =
+            parse_search_moves(&board, &["wtime", "1000", "searchmoves", "e2e4", "e7e5", "d2d4", "depth", "5"]);
+
+        let moves = moves.unwrap().iter().map(|mv| mv.to_uci(&board)).collect::<Vec<_>>();
+        assert_eq!(moves, ["e2e4", "d2d4"]);
+        assert_eq!(rest, ["wtime", "1000", "depth", "5"]);
+
+        let (moves, rest) = parse_search_moves(&board, &["depth", "5"]);
+        assert!(moves.is_none());
+        assert_eq!(rest, ["depth", "5"]);
+
+        let (moves, rest) = parse_search_moves(&board, &["searchmoves", "e7e5", "a1a8", "depth", "5"]);
+        assert!(moves.is_none());
+        assert_eq!(rest, ["depth", "5"]);
+    }
+
+    #[test]
+    fn test_parse_limits_combined() {
+        let limits = parse_limits(Color::White, &["wtime", "60000", "btime", "50000", "winc", "100", "depth", "20"]);
+        assert_eq!(limits.depth, Some(20));
+        assert!(matches!(limits.clock, Some(Clock::Fischer(60000, 100))));
+
+        let limits = parse_limits(Color::Black, &["nodes", "5000", "movetime", "1000", "wtime", "1", "btime", "2"]);
+        assert_eq!(limits.nodes, Some(5000));
+        assert_eq!(limits.movetime, Some(1000));
+        assert!(matches!(limits.clock, Some(Clock::Fischer(2, 0))));
+
+        let limits = parse_limits(Color::White, &["infinite"]);
+        assert!(limits.depth.is_none() && limits.clock.is_none() && !limits.is_timed());
+    }
+
     #[test]
     fn test_position_moves_without_startpos_ignored() {
         let board = test_position_helper(&["moves", "e2e4", "e7e5"]);